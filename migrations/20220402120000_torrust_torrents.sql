@@ -0,0 +1,2 @@
+ALTER TABLE torrust_torrents
+ADD COLUMN deleted_at INT(10);
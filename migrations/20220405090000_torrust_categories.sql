@@ -0,0 +1,2 @@
+ALTER TABLE torrust_categories
+ADD COLUMN parent_id INTEGER REFERENCES torrust_categories(category_id);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS torrust_tags (
+    tag_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(64) NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS torrust_torrent_tags (
+    torrent_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY(torrent_id, tag_id),
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES torrust_tags(tag_id) ON DELETE CASCADE
+)
@@ -0,0 +1,5 @@
+ALTER TABLE torrust_users
+ADD COLUMN banned BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE torrust_users
+ADD COLUMN ban_reason TEXT;
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS torrust_password_reset_tokens (
+    token_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    expires_at INT(10) NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+)
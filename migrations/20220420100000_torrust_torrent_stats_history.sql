@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS torrust_torrent_stats_history (
+    history_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    torrent_id INTEGER NOT NULL,
+    seeders INTEGER NOT NULL,
+    leechers INTEGER NOT NULL,
+    recorded_at INT(10) NOT NULL,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS torrust_torrent_stats_history_torrent_id_recorded_at
+ON torrust_torrent_stats_history (torrent_id, recorded_at);
@@ -0,0 +1,2 @@
+ALTER TABLE torrust_torrents
+ADD COLUMN downloads INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS torrust_reports (
+    report_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    torrent_id INTEGER NOT NULL,
+    reporter_user_id INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'open',
+    created_at INT(10) NOT NULL,
+    UNIQUE(torrent_id, reporter_user_id),
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE,
+    FOREIGN KEY(reporter_user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+)
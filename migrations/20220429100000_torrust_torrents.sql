@@ -0,0 +1,4 @@
+ALTER TABLE torrust_torrents ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'pending';
+
+-- torrents uploaded before moderation existed were already public
+UPDATE torrust_torrents SET status = 'approved';
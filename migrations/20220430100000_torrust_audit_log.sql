@@ -0,0 +1,9 @@
+-- no foreign keys: entries must outlive the users and torrents they refer to
+CREATE TABLE IF NOT EXISTS torrust_audit_log (
+    audit_log_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    admin_user_id INTEGER NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    target TEXT NOT NULL,
+    details TEXT,
+    created_at INT(10) NOT NULL
+)
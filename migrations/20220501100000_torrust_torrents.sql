@@ -0,0 +1 @@
+ALTER TABLE torrust_torrents ADD COLUMN scrape_failures INTEGER NOT NULL DEFAULT 0;
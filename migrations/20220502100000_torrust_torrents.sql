@@ -0,0 +1,3 @@
+ALTER TABLE torrust_torrents ADD COLUMN last_seen_seeders_at INT(10);
+
+UPDATE torrust_torrents SET last_seen_seeders_at = strftime('%s', 'now') WHERE seeders > 0;
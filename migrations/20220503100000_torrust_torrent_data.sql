@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS torrust_torrent_data (
+    torrent_id INTEGER NOT NULL PRIMARY KEY,
+    data BLOB NOT NULL,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+)
@@ -0,0 +1,13 @@
+-- info_hash already has a unique index from its UNIQUE constraint
+
+CREATE INDEX IF NOT EXISTS torrust_torrents_category_id
+ON torrust_torrents (category_id);
+
+CREATE INDEX IF NOT EXISTS torrust_torrents_upload_date
+ON torrust_torrents (upload_date);
+
+CREATE INDEX IF NOT EXISTS torrust_torrents_uploader
+ON torrust_torrents (uploader);
+
+CREATE INDEX IF NOT EXISTS torrust_tracker_keys_user_id_valid_until
+ON torrust_tracker_keys (user_id, valid_until);
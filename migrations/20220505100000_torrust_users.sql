@@ -0,0 +1,5 @@
+-- usernames are unique regardless of case, "Alice" and "alice" are the same account.
+-- this fails if the table already holds names that only differ in case, those have
+-- to be renamed by hand first
+CREATE UNIQUE INDEX IF NOT EXISTS torrust_users_username_nocase
+ON torrust_users (username COLLATE NOCASE);
@@ -0,0 +1 @@
+ALTER TABLE torrust_torrents ADD COLUMN featured BOOLEAN NOT NULL DEFAULT FALSE;
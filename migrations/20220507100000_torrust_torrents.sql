@@ -0,0 +1,4 @@
+ALTER TABLE torrust_torrents ADD COLUMN freeleech BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- freeleech ends at this epoch, NULL keeps it on until it is switched off
+ALTER TABLE torrust_torrents ADD COLUMN freeleech_until INT(10);
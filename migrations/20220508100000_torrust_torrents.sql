@@ -0,0 +1,4 @@
+-- optional release details, eg: "BluRay", "English", "1080p"
+ALTER TABLE torrust_torrents ADD COLUMN source VARCHAR(32);
+ALTER TABLE torrust_torrents ADD COLUMN language VARCHAR(32);
+ALTER TABLE torrust_torrents ADD COLUMN resolution VARCHAR(16);
@@ -0,0 +1,7 @@
+-- no foreign key on added_by: a blacklist entry must outlive the admin who added it
+CREATE TABLE IF NOT EXISTS torrust_blacklisted_hashes (
+    info_hash TEXT NOT NULL PRIMARY KEY,
+    reason TEXT NOT NULL,
+    added_by INTEGER NOT NULL,
+    added_at INT(10) NOT NULL
+)
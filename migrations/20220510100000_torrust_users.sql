@@ -0,0 +1,5 @@
+ALTER TABLE torrust_users ADD COLUMN registration_date INT(10) NOT NULL DEFAULT 0;
+ALTER TABLE torrust_users ADD COLUMN last_login INT(10);
+
+-- the real registration date of existing accounts is unknown, count them from now
+UPDATE torrust_users SET registration_date = strftime('%s', 'now');
@@ -0,0 +1,8 @@
+-- one row per ip, action and fixed time window
+CREATE TABLE IF NOT EXISTS torrust_rate_limits (
+    ip TEXT NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    window_start INT(10) NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY(ip, action, window_start)
+)
@@ -0,0 +1,8 @@
+-- query_json holds a serialized `TorrentQuery`
+CREATE TABLE IF NOT EXISTS torrust_saved_searches (
+    saved_search_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    query_json TEXT NOT NULL,
+    created_at INT(10) NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+)
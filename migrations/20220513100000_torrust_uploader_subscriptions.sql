@@ -0,0 +1,8 @@
+-- uploaders are referred to by username, the same way torrust_torrents.uploader is
+CREATE TABLE IF NOT EXISTS torrust_uploader_subscriptions (
+    subscriber_user_id INTEGER NOT NULL,
+    uploader_username VARCHAR(32) NOT NULL,
+    created_at INT(10) NOT NULL,
+    PRIMARY KEY(subscriber_user_id, uploader_username),
+    FOREIGN KEY(subscriber_user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+)
@@ -0,0 +1 @@
+ALTER TABLE torrust_torrents ADD COLUMN views INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- max bytes of approved uploads per user, NULL is unlimited
+ALTER TABLE torrust_users ADD COLUMN upload_quota INTEGER DEFAULT NULL;
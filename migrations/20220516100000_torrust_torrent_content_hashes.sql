@@ -0,0 +1,9 @@
+-- sha-1 of a torrent's sorted file list, torrents sharing it have the same files and can be cross-seeded
+CREATE TABLE IF NOT EXISTS torrust_torrent_content_hashes (
+    torrent_id INTEGER NOT NULL PRIMARY KEY,
+    content_hash CHAR(40) NOT NULL,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS torrust_torrent_content_hashes_content_hash
+    ON torrust_torrent_content_hashes (content_hash);
@@ -0,0 +1,3 @@
+-- taken from the info dictionary on upload, NULL for torrents uploaded before they were stored
+ALTER TABLE torrust_torrents ADD COLUMN piece_length INTEGER DEFAULT NULL;
+ALTER TABLE torrust_torrents ADD COLUMN piece_count INTEGER DEFAULT NULL;
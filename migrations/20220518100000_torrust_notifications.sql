@@ -0,0 +1,18 @@
+-- copied out of query_json so an upload only checks the saved searches that can match its category
+ALTER TABLE torrust_saved_searches ADD COLUMN category_id INTEGER DEFAULT NULL;
+UPDATE torrust_saved_searches SET category_id = json_extract(query_json, '$.category_id');
+CREATE INDEX IF NOT EXISTS torrust_saved_searches_category_id ON torrust_saved_searches (category_id);
+
+-- an upload that matched one of a user's saved searches, waiting to be delivered
+CREATE TABLE IF NOT EXISTS torrust_notifications (
+    notification_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    saved_search_id INTEGER NOT NULL,
+    torrent_id INTEGER NOT NULL,
+    created_at INT(10) NOT NULL,
+    UNIQUE(saved_search_id, torrent_id),
+    FOREIGN KEY(user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(saved_search_id) REFERENCES torrust_saved_searches(saved_search_id) ON DELETE CASCADE,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS torrust_notifications_user_id ON torrust_notifications (user_id);
@@ -83,7 +83,8 @@ impl AuthorizationService {
             Err(e) => Err(e)
         }?;
 
-        match self.database.get_user_with_username(&claims.sub).await {
+        match self.database.get_user_with_username(&claims.sub).await? {
+            Some(user) if user.banned => Err(ServiceError::UserBanned),
             Some(user) => Ok(user),
             None => Err(ServiceError::AccountNotFound)
         }
@@ -1,8 +1,10 @@
 use std::fs;
-use config::{ConfigError, Config, File};
+use config::{ConfigError, Config, Environment, File};
 use std::path::Path;
 use serde::{Serialize, Deserialize};
 use tokio::sync::RwLock;
+use crate::content::filter::MatchMode;
+use crate::database::DeletedUserTorrents;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Website {
@@ -12,9 +14,64 @@ pub struct Website {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tracker {
     pub url: String,
+    // public mirrors, announced after `url` (or the user's personal announce url) in magnet
+    // links and downloaded .torrent files
+    #[serde(default)]
+    pub announce_urls: Vec<String>,
     pub api_url: String,
     pub token: String,
     pub token_valid_seconds: u64,
+    // keys expiring within this window are replaced by a new one
+    #[serde(default = "Tracker::default_token_renewal_seconds")]
+    pub token_renewal_seconds: u64,
+    // max number of torrent info requests in flight while updating torrents
+    #[serde(default = "Tracker::default_scrape_concurrency")]
+    pub scrape_concurrency: usize,
+    #[serde(default = "Tracker::default_scrape_timeout_seconds")]
+    pub scrape_timeout_seconds: u64,
+    // BEP 48 http scrape url, eg: "https://tracker.example.com/scrape",
+    // torrent info is fetched through the tracker api when this is not set
+    #[serde(default)]
+    pub scrape_url: Option<String>,
+    #[serde(default = "Tracker::default_scrape_batch_size")]
+    pub scrape_batch_size: usize,
+    // failed scrapes are retried with an exponential backoff: base, base * 2, base * 4, .. up to max
+    #[serde(default = "Tracker::default_scrape_retries")]
+    pub scrape_retries: u32,
+    #[serde(default = "Tracker::default_scrape_backoff_base_ms")]
+    pub scrape_backoff_base_ms: u64,
+    #[serde(default = "Tracker::default_scrape_backoff_max_ms")]
+    pub scrape_backoff_max_ms: u64,
+}
+
+impl Tracker {
+    fn default_token_renewal_seconds() -> u64 {
+        604_800
+    }
+
+    fn default_scrape_concurrency() -> usize {
+        50
+    }
+
+    fn default_scrape_timeout_seconds() -> u64 {
+        10
+    }
+
+    fn default_scrape_batch_size() -> usize {
+        50
+    }
+
+    fn default_scrape_retries() -> u32 {
+        2
+    }
+
+    fn default_scrape_backoff_base_ms() -> u64 {
+        500
+    }
+
+    fn default_scrape_backoff_max_ms() -> u64 {
+        10_000
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +91,60 @@ pub struct Auth {
 pub struct Database {
     pub connect_url: String,
     pub torrent_info_update_interval: u64,
+    // store a seeders/leechers snapshot on every torrent info update
+    #[serde(default)]
+    pub stats_history_enabled: bool,
+    // snapshots older than this are deleted by the hourly cleanup
+    #[serde(default = "Database::default_stats_history_keep_days")]
+    pub stats_history_keep_days: u64,
+    // soft delete torrents that had no seeders for `dead_torrent_days`
+    #[serde(default)]
+    pub prune_dead_torrents: bool,
+    #[serde(default = "Database::default_dead_torrent_days")]
+    pub dead_torrent_days: u64,
+    #[serde(default = "Database::default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default)]
+    pub min_connections: u32,
+    #[serde(default = "Database::default_acquire_timeout_secs")]
+    pub acquire_timeout_secs: u64,
+    // sqlite journal_mode, busy_timeout and foreign_keys pragmas
+    #[serde(default = "Database::default_journal_mode")]
+    pub journal_mode: String,
+    #[serde(default = "Database::default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+    #[serde(default = "Database::default_foreign_keys")]
+    pub foreign_keys: bool,
+}
+
+impl Database {
+    fn default_max_connections() -> u32 {
+        crate::database::DEFAULT_MAX_CONNECTIONS
+    }
+
+    fn default_acquire_timeout_secs() -> u64 {
+        crate::database::DEFAULT_ACQUIRE_TIMEOUT
+    }
+
+    fn default_dead_torrent_days() -> u64 {
+        90
+    }
+
+    fn default_stats_history_keep_days() -> u64 {
+        90
+    }
+
+    fn default_journal_mode() -> String {
+        crate::database::DEFAULT_JOURNAL_MODE.to_string()
+    }
+
+    fn default_busy_timeout_ms() -> u64 {
+        crate::database::DEFAULT_BUSY_TIMEOUT
+    }
+
+    fn default_foreign_keys() -> bool {
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +152,54 @@ pub struct Storage {
     pub upload_path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Moderation {
+    // new uploads are listed right away instead of waiting for a moderator
+    pub auto_approve_uploads: bool,
+    // "reassign" keeps the uploads of a deleted account listed, "soft_delete" removes them too
+    #[serde(default)]
+    pub deleted_user_torrents: DeletedUserTorrents,
+}
+
+impl Default for Moderation {
+    fn default() -> Self {
+        Moderation {
+            auto_approve_uploads: true,
+            deleted_user_torrents: DeletedUserTorrents::default()
+        }
+    }
+}
+
+// uploads with a title containing one of `blocked_terms` are rejected, an empty list disables the filter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TitleFilter {
+    #[serde(default)]
+    pub blocked_terms: Vec<String>,
+    // "whole_word" or "substring"
+    #[serde(default)]
+    pub match_mode: MatchMode,
+}
+
+// at most `*_max` requests per ip in every window of `*_window_secs` seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimits {
+    pub register_max: i64,
+    pub register_window_secs: i64,
+    pub upload_max: i64,
+    pub upload_window_secs: i64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        RateLimits {
+            register_max: 5,
+            register_window_secs: 3600,
+            upload_max: 20,
+            upload_window_secs: 3600
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mail {
     pub email_verification_enabled: bool,
@@ -61,6 +220,38 @@ pub struct TorrustConfig {
     pub database: Database,
     pub storage: Storage,
     pub mail: Mail,
+    #[serde(default)]
+    pub moderation: Moderation,
+    #[serde(default)]
+    pub rate_limits: RateLimits,
+    #[serde(default)]
+    pub title_filter: TitleFilter,
+}
+
+impl TorrustConfig {
+    // settings that deserialize fine when left empty but make the index unusable,
+    // all of them are reported at once
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let required = [
+            ("tracker.url", &self.tracker.url),
+            ("tracker.api_url", &self.tracker.api_url),
+            ("tracker.token", &self.tracker.token),
+            ("auth.secret_key", &self.auth.secret_key),
+            ("database.connect_url", &self.database.connect_url),
+            ("storage.upload_path", &self.storage.upload_path),
+        ];
+
+        let missing: Vec<&str> = required.iter()
+            .filter(|(_, value)| value.trim().is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(ConfigError::Message(format!("Missing required settings: {}.", missing.join(", "))))
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -83,9 +274,18 @@ impl Configuration {
             },
             tracker: Tracker {
                 url: "udp://localhost:6969".to_string(),
+                announce_urls: vec![],
                 api_url: "http://localhost:1212".to_string(),
                 token: "MyAccessToken".to_string(),
-                token_valid_seconds: 7257600
+                token_valid_seconds: 7257600,
+                token_renewal_seconds: 604800,
+                scrape_concurrency: 50,
+                scrape_timeout_seconds: 10,
+                scrape_url: None,
+                scrape_batch_size: 50,
+                scrape_retries: 2,
+                scrape_backoff_base_ms: 500,
+                scrape_backoff_max_ms: 10000
             },
             net: Network {
                 port: 3000,
@@ -98,7 +298,17 @@ impl Configuration {
             },
             database: Database {
                 connect_url: "sqlite://data.db?mode=rwc".to_string(),
-                torrent_info_update_interval: 3600
+                torrent_info_update_interval: 3600,
+                stats_history_enabled: false,
+                stats_history_keep_days: Database::default_stats_history_keep_days(),
+                prune_dead_torrents: false,
+                dead_torrent_days: Database::default_dead_torrent_days(),
+                max_connections: crate::database::DEFAULT_MAX_CONNECTIONS,
+                min_connections: 0,
+                acquire_timeout_secs: crate::database::DEFAULT_ACQUIRE_TIMEOUT,
+                journal_mode: crate::database::DEFAULT_JOURNAL_MODE.to_string(),
+                busy_timeout_ms: crate::database::DEFAULT_BUSY_TIMEOUT,
+                foreign_keys: Database::default_foreign_keys()
             },
             storage: Storage {
                 upload_path: "./uploads".to_string()
@@ -111,7 +321,10 @@ impl Configuration {
                 password: "".to_string(),
                 server: "".to_string(),
                 port: 25
-            }
+            },
+            moderation: Moderation::default(),
+            rate_limits: RateLimits::default(),
+            title_filter: TitleFilter::default()
         };
 
         Configuration {
@@ -134,11 +347,17 @@ impl Configuration {
             return Err(ConfigError::Message(format!("Please edit the config.TOML in the root folder and restart the tracker.")))
         }
 
+        // environment variables override single settings from the file, a double underscore
+        // separates the section from the field, eg: TORURO_DATABASE__CONNECT_URL
+        config.merge(Environment::with_prefix("TORURO").separator("__"))?;
+
         let torrust_config: TorrustConfig = match config.try_into() {
             Ok(data) => Ok(data),
             Err(e) => Err(ConfigError::Message(format!("Errors while processing config: {}.", e))),
         }?;
 
+        torrust_config.validate()?;
+
         Ok(Configuration {
             settings: RwLock::new(torrust_config)
         })
@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+use crate::config::TitleFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    // "ass" matches "bad ass" and "ass-kicking" but not "classic"
+    #[default]
+    WholeWord,
+    // "ass" matches anywhere, "classic" included
+    Substring,
+}
+
+// checks titles against the configured blocklist, matching ignores case
+pub struct ContentFilter {
+    terms: Vec<String>,
+    mode: MatchMode,
+}
+
+impl ContentFilter {
+    pub fn new(terms: &[String], mode: MatchMode) -> ContentFilter {
+        let terms = terms.iter()
+            .map(|term| term.trim().to_lowercase())
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        ContentFilter {
+            terms,
+            mode,
+        }
+    }
+
+    pub fn from_config(cfg: &TitleFilter) -> ContentFilter {
+        ContentFilter::new(&cfg.blocked_terms, cfg.match_mode)
+    }
+
+    // always false when the blocklist is empty
+    pub fn contains_blocked_terms(&self, title: &str) -> bool {
+        if self.terms.is_empty() { return false }
+
+        let title = title.to_lowercase();
+
+        self.terms.iter().any(|term| match self.mode {
+            MatchMode::WholeWord => contains_word(&title, term),
+            MatchMode::Substring => title.contains(term.as_str()),
+        })
+    }
+}
+
+// a match counts when it isn't directly preceded or followed by a letter or digit
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
@@ -0,0 +1,16 @@
+use pulldown_cmark::{html, Options, Parser};
+
+// renders user supplied markdown to html that is safe to embed in a page,
+// anything not on ammonia's allowlist (scripts, event handlers, ..) is stripped
+pub fn render_markdown(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let parser = Parser::new_ext(markdown, options);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::clean(&unsafe_html)
+}
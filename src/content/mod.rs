@@ -0,0 +1,5 @@
+pub mod filter;
+pub mod markdown;
+
+pub use filter::ContentFilter;
+pub use markdown::render_markdown;
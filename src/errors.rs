@@ -1,9 +1,9 @@
-use std::borrow::Cow;
 use derive_more::{Display, Error};
 use actix_web::{ResponseError, HttpResponse, HttpResponseBuilder};
 use actix_web::http::{header, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::error;
+use crate::request_id::current_request_id;
 
 pub type ServiceResult<V> = std::result::Result<V, ServiceError>;
 
@@ -64,6 +64,9 @@ pub enum ServiceError {
     #[display(fmt = "Please verify your email before logging in")]
     EmailNotVerified,
 
+    #[display(fmt = "This account has been banned")]
+    UserBanned,
+
     /// when the a token name is already taken
     /// token not found
     #[display(fmt = "Token not found. Please sign in.")]
@@ -80,37 +83,106 @@ pub enum ServiceError {
     #[display(fmt = "Torrent not found.")]
     TorrentNotFound,
 
+    #[display(fmt = "Torrent file not found.")]
+    TorrentFileMissing,
+
+    #[display(fmt = "Info hash is not valid")]
+    InvalidInfoHash,
+
+    #[display(fmt = "Route may only contain lowercase letters, numbers and dashes")]
+    InvalidRoute,
+
+    #[display(fmt = "Start of the date range must not be after its end")]
+    InvalidDateRange,
+
+    #[display(fmt = "Size filters must not be negative and the minimum must not exceed the maximum")]
+    InvalidSizeRange,
+
+    #[display(fmt = "File size must be greater than zero")]
+    InvalidFileSize,
+
     #[display(fmt = "Uploaded torrent is not valid")]
     InvalidTorrentFile,
 
     #[display(fmt = "Only .torrent files can be uploaded")]
     InvalidFileType,
 
+    #[display(fmt = "Comment can't be empty.")]
+    EmptyComment,
+
+    #[display(fmt = "Comment not found.")]
+    CommentNotFound,
+
+    #[display(fmt = "Report not found.")]
+    ReportNotFound,
+
     #[display(fmt = "Bad request.")]
     BadRequest,
 
     #[display(fmt = "Selected category does not exist")]
     InvalidCategory,
 
+    #[display(fmt = "Too many requests, try again later.")]
+    TooManyRequests,
+
+    #[display(fmt = "The last administrator can't be demoted.")]
+    CannotRemoveLastAdmin,
+
     #[display(fmt = "Unauthorized action.")]
     Unauthorized,
 
+    #[display(fmt = "This torrent is not allowed on this index.")]
+    HashBlacklisted,
+
     #[display(fmt = "This torrent already exists in our database.")]
-    InfoHashAlreadyExists,
+    TorrentAlreadyExists,
+
+    #[display(fmt = "This title is not allowed on this index.")]
+    TitleRejected,
+
+    #[display(fmt = "A torrent with this title already exists.")]
+    TorrentTitleTaken,
 
     #[display(fmt = "Sorry, we have an error with our tracker connection.")]
     TrackerOffline,
 
+    #[display(fmt = "Tracker key not found.")]
+    TrackerKeyNotFound,
+
+    #[display(fmt = "Database is unavailable.")]
+    DatabaseUnavailable,
+
     #[display(fmt = "Failed to send verification email.")]
     FailedToSendVerificationEmail,
 
     #[display(fmt = "Category already exists..")]
-    CategoryExists,
+    CategoryAlreadyExists,
+
+    #[display(fmt = "Category still has torrents.")]
+    CategoryInUse,
+
+    #[display(fmt = "Category still has subcategories.")]
+    CategoryHasChildren,
+
+    #[display(fmt = "Category not found.")]
+    CategoryNotFound,
+
+    #[display(fmt = "Tag already exists.")]
+    TagAlreadyExists,
+
+    #[display(fmt = "Tag not found.")]
+    TagNotFound,
+
+    #[display(fmt = "This upload would exceed your upload quota.")]
+    QuotaExceeded,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ErrorToResponse {
     pub error: String,
+    // quoted in bug reports to find the matching log lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl ResponseError for ServiceError {
@@ -135,27 +207,54 @@ impl ResponseError for ServiceError {
             ServiceError::UsernameInvalid => StatusCode::BAD_REQUEST,
             ServiceError::EmailTaken => StatusCode::BAD_REQUEST,
             ServiceError::EmailNotVerified => StatusCode::FORBIDDEN,
+            ServiceError::UserBanned => StatusCode::FORBIDDEN,
 
             ServiceError::TokenNotFound => StatusCode::UNAUTHORIZED,
             ServiceError::TokenExpired => StatusCode::UNAUTHORIZED,
             ServiceError::TokenInvalid => StatusCode::UNAUTHORIZED,
 
             ServiceError::TorrentNotFound => StatusCode::BAD_REQUEST,
+            ServiceError::TorrentFileMissing => StatusCode::NOT_FOUND,
 
+            ServiceError::InvalidInfoHash => StatusCode::BAD_REQUEST,
+            ServiceError::InvalidRoute => StatusCode::BAD_REQUEST,
+            ServiceError::InvalidDateRange => StatusCode::BAD_REQUEST,
+            ServiceError::InvalidSizeRange => StatusCode::BAD_REQUEST,
+            ServiceError::InvalidFileSize => StatusCode::BAD_REQUEST,
             ServiceError::InvalidTorrentFile => StatusCode::BAD_REQUEST,
             ServiceError::InvalidFileType => StatusCode::BAD_REQUEST,
 
+            ServiceError::EmptyComment => StatusCode::BAD_REQUEST,
+            ServiceError::CommentNotFound => StatusCode::NOT_FOUND,
+            ServiceError::ReportNotFound => StatusCode::NOT_FOUND,
+
             ServiceError::BadRequest => StatusCode::BAD_REQUEST,
 
             ServiceError::InvalidCategory => StatusCode::BAD_REQUEST,
 
             ServiceError::Unauthorized => StatusCode::FORBIDDEN,
+            ServiceError::CannotRemoveLastAdmin => StatusCode::BAD_REQUEST,
+            ServiceError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
 
-            ServiceError::InfoHashAlreadyExists => StatusCode::BAD_REQUEST,
+            ServiceError::TorrentAlreadyExists => StatusCode::BAD_REQUEST,
+            ServiceError::TorrentTitleTaken => StatusCode::BAD_REQUEST,
+            ServiceError::HashBlacklisted => StatusCode::FORBIDDEN,
+            ServiceError::TitleRejected => StatusCode::BAD_REQUEST,
 
             ServiceError::TrackerOffline => StatusCode::INTERNAL_SERVER_ERROR,
+            ServiceError::TrackerKeyNotFound => StatusCode::NOT_FOUND,
+
+            ServiceError::DatabaseUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+
+            ServiceError::CategoryAlreadyExists => StatusCode::BAD_REQUEST,
+            ServiceError::CategoryInUse => StatusCode::BAD_REQUEST,
+            ServiceError::CategoryHasChildren => StatusCode::BAD_REQUEST,
+            ServiceError::CategoryNotFound => StatusCode::NOT_FOUND,
+
+            ServiceError::TagAlreadyExists => StatusCode::BAD_REQUEST,
+            ServiceError::TagNotFound => StatusCode::NOT_FOUND,
 
-            ServiceError::CategoryExists => StatusCode::BAD_REQUEST,
+            ServiceError::QuotaExceeded => StatusCode::FORBIDDEN,
 
             _ => StatusCode::INTERNAL_SERVER_ERROR
         }
@@ -167,6 +266,7 @@ impl ResponseError for ServiceError {
             .body(
                 serde_json::to_string(&ErrorToResponse {
                     error: self.to_string(),
+                    request_id: current_request_id(),
                 })
                     .unwrap(),
             )
@@ -174,50 +274,80 @@ impl ResponseError for ServiceError {
     }
 }
 
-impl From<sqlx::Error> for ServiceError {
-    fn from(e: sqlx::Error) -> Self {
-        eprintln!("{:?}", e);
-
-        if let Some(err) = e.as_database_error() {
-            return if err.code() == Some(Cow::from("2067")) {
-                if err.message().contains("torrust_torrents.info_hash") {
-                    ServiceError::InfoHashAlreadyExists
-                } else {
-                    ServiceError::InternalServerError
-                }
+// sqlite extended result codes for constraint violations
+const SQLITE_CONSTRAINT_FOREIGNKEY: &str = "787";
+const SQLITE_CONSTRAINT_UNIQUE: &str = "2067";
+
+// turns constraint violations into errors a client can act on, anything else is an internal error
+pub fn map_sqlx_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!(error = ?e, "database error");
+
+    let err = match e.as_database_error() {
+        Some(err) => err,
+        None => return ServiceError::InternalServerError
+    };
+
+    match err.code().as_deref() {
+        Some(SQLITE_CONSTRAINT_UNIQUE) => {
+            let message = err.message();
+
+            if message.contains("torrust_torrents.info_hash") {
+                ServiceError::TorrentAlreadyExists
+            } else if message.contains("torrust_torrents.title") {
+                ServiceError::TorrentTitleTaken
+            } else if message.contains("torrust_users.username") {
+                ServiceError::UsernameTaken
+            } else if message.contains("torrust_users.email") {
+                ServiceError::EmailTaken
+            } else if message.contains("torrust_categories.name") {
+                ServiceError::CategoryAlreadyExists
+            } else if message.contains("torrust_tags.name") {
+                ServiceError::TagAlreadyExists
             } else {
-                ServiceError::TorrentNotFound
+                ServiceError::InternalServerError
             }
         }
+        // sqlite doesn't say which key failed, callers that know use `is_foreign_key_violation`
+        Some(SQLITE_CONSTRAINT_FOREIGNKEY) => ServiceError::BadRequest,
+        _ => ServiceError::InternalServerError
+    }
+}
 
-        ServiceError::InternalServerError
+// an insert or update referenced a row that doesn't exist
+pub fn is_foreign_key_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error().and_then(|err| err.code()).as_deref() == Some(SQLITE_CONSTRAINT_FOREIGNKEY)
+}
+
+impl From<sqlx::Error> for ServiceError {
+    fn from(e: sqlx::Error) -> Self {
+        map_sqlx_error(e)
     }
 }
 
 impl From<pbkdf2::password_hash::Error> for ServiceError {
     fn from(e: pbkdf2::password_hash::Error) -> Self {
-        eprintln!("{}", e);
+        tracing::error!(error = %e);
         ServiceError::InternalServerError
     }
 }
 
 impl From<std::io::Error> for ServiceError {
     fn from(e: std::io::Error) -> Self {
-        eprintln!("{}", e);
+        tracing::error!(error = %e);
         ServiceError::InternalServerError
     }
 }
 
 impl From<Box<dyn error::Error>> for ServiceError {
     fn from(e: Box<dyn error::Error>) -> Self {
-        eprintln!("{}", e);
+        tracing::error!(error = %e);
         ServiceError::InternalServerError
     }
 }
 
 impl From<serde_json::Error> for ServiceError {
     fn from(e: serde_json::Error) -> Self {
-        eprintln!("{}", e);
+        tracing::error!(error = %e);
         ServiceError::InternalServerError
     }
 }
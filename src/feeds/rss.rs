@@ -0,0 +1,82 @@
+use crate::models::torrent::TorrentListing;
+
+const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+pub struct Channel<'a> {
+    pub title: &'a str,
+    pub link: &'a str,
+    pub description: &'a str,
+}
+
+// builds an RSS 2.0 document, `base_url` is used to link back to each torrent
+pub fn build_feed(channel: &Channel, base_url: &str, torrents: &[TorrentListing]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<rss version="2.0"><channel>"#);
+    xml.push_str(&format!("<title>{}</title>", escape_xml(channel.title)));
+    xml.push_str(&format!("<link>{}</link>", escape_xml(channel.link)));
+    xml.push_str(&format!("<description>{}</description>", escape_xml(channel.description)));
+
+    for torrent in torrents {
+        let link = format!("{}/torrent/{}", base_url, torrent.torrent_id);
+
+        xml.push_str("<item>");
+        xml.push_str(&format!("<title>{}</title>", escape_xml(&torrent.title)));
+        xml.push_str(&format!("<link>{}</link>", escape_xml(&link)));
+        xml.push_str(&format!("<description>{}</description>", escape_xml(torrent.description.as_deref().unwrap_or(""))));
+        xml.push_str(&format!("<pubDate>{}</pubDate>", rfc822_date(torrent.upload_date)));
+        xml.push_str(&format!(r#"<guid isPermaLink="false">{}</guid>"#, escape_xml(&torrent.info_hash)));
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+
+    xml
+}
+
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// formats a unix timestamp as an RFC 822 date, eg: "Sat, 02 Apr 2022 12:00:00 GMT"
+fn rfc822_date(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        DAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,69 @@
+use crate::database::Database;
+use crate::errors::ServiceError;
+use crate::feeds::rss::escape_xml;
+use crate::utils::time::epoch_to_rfc3339;
+
+// the sitemap protocol allows at most 50,000 urls per sitemap
+pub const MAX_SITEMAP_URLS: i64 = 50_000;
+
+// the sitemap for `/sitemap.xml`. small indexes get a single `<urlset>`, larger ones a
+// `<sitemapindex>` pointing at `/sitemap/{page}.xml`, see `generate_child_sitemap`
+pub async fn generate_sitemap(database: &Database, base_url: &str) -> Result<String, ServiceError> {
+    let count = database.count_listed_torrents().await?;
+
+    // the home page takes one of the urls
+    if count < MAX_SITEMAP_URLS {
+        let torrents = database.get_sitemap_torrents(MAX_SITEMAP_URLS, 0).await?;
+        return Ok(build_urlset(base_url, &torrents, true))
+    }
+
+    let pages = (count + MAX_SITEMAP_URLS - 1) / MAX_SITEMAP_URLS;
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+
+    for page in 0..pages {
+        xml.push_str(&format!("<sitemap><loc>{}</loc></sitemap>", escape_xml(&format!("{}/sitemap/{}.xml", base_url, page))));
+    }
+
+    xml.push_str("</sitemapindex>");
+
+    Ok(xml)
+}
+
+// one page of a split sitemap, `None` when the page is past the last torrent.
+// the home page is left out so every page stays within the limit
+pub async fn generate_child_sitemap(database: &Database, base_url: &str, page: i64) -> Result<Option<String>, ServiceError> {
+    if page < 0 { return Ok(None) }
+
+    let torrents = database.get_sitemap_torrents(MAX_SITEMAP_URLS, page * MAX_SITEMAP_URLS).await?;
+
+    if torrents.is_empty() { return Ok(None) }
+
+    Ok(Some(build_urlset(base_url, &torrents, false)))
+}
+
+// `torrents` are (torrent_id, upload_date) pairs
+fn build_urlset(base_url: &str, torrents: &[(i64, i64)], include_home: bool) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+
+    if include_home {
+        xml.push_str(&format!("<url><loc>{}/</loc><changefreq>hourly</changefreq></url>", escape_xml(base_url)));
+    }
+
+    for (torrent_id, upload_date) in torrents {
+        xml.push_str("<url>");
+        xml.push_str(&format!("<loc>{}</loc>", escape_xml(&format!("{}/torrent/{}", base_url, torrent_id))));
+        if let Some(lastmod) = epoch_to_rfc3339(*upload_date) {
+            xml.push_str(&format!("<lastmod>{}</lastmod>", lastmod));
+        }
+        // the page shows the seeders and leechers, which change all the time
+        xml.push_str("<changefreq>daily</changefreq>");
+        xml.push_str("</url>");
+    }
+
+    xml.push_str("</urlset>");
+
+    xml
+}
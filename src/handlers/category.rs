@@ -17,15 +17,10 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
 }
 
 pub async fn get_categories(app_data: WebAppData) -> ServiceResult<impl Responder> {
-    // Count torrents with category
-    let res = sqlx::query_as::<_, CategoryResponse>(
-        r#"SELECT name, COUNT(tt.category_id) as num_torrents
-           FROM torrust_categories tc
-           LEFT JOIN torrust_torrents tt on tc.category_id = tt.category_id
-           GROUP BY tc.name"#
-    )
-        .fetch_all(&app_data.database.pool)
-        .await?;
+    let res: Vec<CategoryResponse> = app_data.database.get_category_counts().await?
+        .into_iter()
+        .map(|(name, num_torrents)| CategoryResponse { name, num_torrents: Some(num_torrents) })
+        .collect();
 
     Ok(HttpResponse::Ok().json(OkResponse {
         data: res
@@ -44,20 +39,9 @@ pub async fn add_category(req: HttpRequest, payload: web::Json<Category>, app_da
     // check if user is administrator
     if !user.administrator { return Err(ServiceError::Unauthorized) }
 
-    let res = sqlx::query!(
-        "INSERT INTO torrust_categories (name) VALUES ($1)",
-        payload.name,
-    )
-        .execute(&app_data.database.pool)
-        .await;
+    let _ = app_data.database.add_category(&payload.name).await?;
 
-    if let Err(sqlx::Error::Database(err)) = res {
-        return if err.message().contains("UNIQUE") {
-            Err(ServiceError::CategoryExists)
-        } else {
-            Err(ServiceError::InternalServerError)
-        }
-    }
+    app_data.database.log_admin_action(user.user_id, "add_category", &payload.name, None).await?;
 
     Ok(HttpResponse::Ok().json(OkResponse {
         data: payload.name.clone()
@@ -71,12 +55,9 @@ pub async fn delete_category(req: HttpRequest, payload: web::Json<Category>, app
     // check if user is administrator
     if !user.administrator { return Err(ServiceError::Unauthorized) }
 
-    let _res = sqlx::query!(
-        "DELETE FROM torrust_categories WHERE name = $1",
-        payload.name,
-    )
-        .execute(&app_data.database.pool)
-        .await?;
+    app_data.database.delete_category(&payload.name).await?;
+
+    app_data.database.log_admin_action(user.user_id, "delete_category", &payload.name, None).await?;
 
     Ok(HttpResponse::Ok().json(OkResponse {
         data: payload.name.clone()
@@ -0,0 +1,20 @@
+use actix_web::{HttpResponse, Responder, web};
+use crate::common::WebAppData;
+use crate::errors::ServiceResult;
+use crate::models::response::OkResponse;
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/health")
+            .route(web::get().to(get_health))
+    );
+}
+
+// readiness probe, responds with 503 when the database can't serve queries
+pub async fn get_health(app_data: WebAppData) -> ServiceResult<impl Responder> {
+    app_data.database.ping().await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: "ok"
+    }))
+}
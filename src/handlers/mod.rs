@@ -1,13 +1,42 @@
-use actix_web::web;
+use std::net::SocketAddr;
+use actix_web::{HttpRequest, web};
+use crate::common::WebAppData;
+use crate::errors::{ServiceError, ServiceResult};
 
 pub mod user;
 pub mod torrent;
 pub mod category;
 pub mod settings;
+pub mod health;
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     user::init_routes(cfg);
     torrent::init_routes(cfg);
     category::init_routes(cfg);
     settings::init_routes(cfg);
+    health::init_routes(cfg);
+}
+
+// rejects the request with `TooManyRequests` once the client's ip used up its limit for `action`.
+// the ip comes from the Forwarded / X-Forwarded-For headers when set, so the index must
+// sit behind a proxy that overwrites them
+pub async fn check_rate_limit(req: &HttpRequest, app_data: &WebAppData, action: &str, max: i64, window_secs: i64) -> ServiceResult<()> {
+    let ip = client_ip(req);
+
+    if !app_data.database.check_and_increment_rate_limit(&ip, action, max, window_secs).await? {
+        return Err(ServiceError::TooManyRequests)
+    }
+
+    Ok(())
+}
+
+pub fn client_ip(req: &HttpRequest) -> String {
+    let conn_info = req.connection_info();
+    let addr = conn_info.realip_remote_addr().unwrap_or("unknown");
+
+    // the peer address includes the port, which changes with every connection
+    match addr.parse::<SocketAddr>() {
+        Ok(socket_addr) => socket_addr.ip().to_string(),
+        Err(_) => addr.to_string()
+    }
 }
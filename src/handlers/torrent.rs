@@ -1,16 +1,27 @@
 use actix_multipart::Multipart;
 use actix_web::{HttpRequest, HttpResponse, Responder, web};
 use actix_web::web::{Query};
-use futures::{AsyncWriteExt, StreamExt, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize};
 use crate::errors::{ServiceError, ServiceResult};
-use crate::models::response::{NewTorrentResponse, OkResponse, TorrentResponse, TorrentsResponse};
-use crate::models::torrent::{TorrentListing, TorrentRequest};
-use crate::utils::parse_torrent;
+use crate::models::response::{NewTorrentResponse, OkResponse, TorrentResponse, TorrentsResponse, UploadTorrentResponse};
+use crate::models::torrent::{NewTorrent, SortField, SortOrder, TorrentListing, TorrentRequest, TorrentStatus};
+use crate::utils::{bencode, parse_torrent};
+use crate::utils::magnet::build_magnet;
 use crate::common::{WebAppData};
+use crate::feeds::rss::{self, Channel};
+use crate::feeds::sitemap;
+use crate::content::ContentFilter;
+use crate::handlers::{check_rate_limit, client_ip};
+use crate::database::{Database, ExportFormat};
 use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::io::AsyncReadExt;
+use tokio::sync::oneshot;
 use std::io::{Write};
-use crate::models::torrent_file::{Torrent, File};
+use crate::models::torrent_file::File;
 use crate::AsCSV;
 use std::option::Option::Some;
 use sqlx::{FromRow};
@@ -27,13 +38,38 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
                 .route(web::put().to(update_torrent))
                 .route(web::delete().to(delete_torrent)))
     );
+    cfg.service(web::resource("/sitemap.xml")
+        .route(web::get().to(get_sitemap)));
+    cfg.service(web::resource("/sitemap/{page}.xml")
+        .route(web::get().to(get_child_sitemap)));
     cfg.service(
         web::scope("/torrents")
             .service(web::resource("")
                 .route(web::get().to(get_torrents)))
+            .service(web::resource("/rss")
+                .route(web::get().to(get_rss_feed)))
+            .service(web::resource("/live")
+                .route(web::get().to(get_live_torrents)))
+            .service(web::resource("/export")
+                .route(web::get().to(export_torrents)))
+            .service(web::resource("/import")
+                .app_data(web::PayloadConfig::new(IMPORT_MAX_SIZE))
+                .route(web::post().to(import_torrents)))
     );
 }
 
+const RSS_FEED_SIZE: i64 = 50;
+// repeated views of a torrent from the same ip within this window count once
+pub const VIEW_DEDUP_WINDOW_SECS: i64 = 3600;
+// largest dump accepted by the import, in bytes
+const IMPORT_MAX_SIZE: usize = 64 * 1024 * 1024;
+// uploads are flagged, not blocked, when an existing title scores at least this similar
+const SIMILAR_TITLE_THRESHOLD: f64 = 0.85;
+// a comment is sent on a quiet live stream this often, so proxies don't close the connection
+const LIVE_KEEPALIVE_SECS: u64 = 30;
+// the export is sent in chunks of at most this many bytes, and written at most this far ahead of the download
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
 #[derive(Debug, Deserialize)]
 pub struct DisplayInfo {
     page_size: Option<i32>,
@@ -41,6 +77,8 @@ pub struct DisplayInfo {
     sort: Option<String>,
     // expects comma separated string, eg: "?categories=movie,other,app"
     categories: Option<String>,
+    // also match torrents in subcategories of the selected categories
+    include_subcategories: Option<bool>,
     search: Option<String>,
 }
 
@@ -54,6 +92,9 @@ pub struct CreateTorrent {
     pub title: String,
     pub description: String,
     pub category: String,
+    pub source: Option<String>,
+    pub language: Option<String>,
+    pub resolution: Option<String>,
 }
 
 impl CreateTorrent {
@@ -77,39 +118,28 @@ pub async fn get_torrents(params: Query<DisplayInfo>, app_data: WebAppData) -> S
         Some(v) => format!("%{}%", v)
     };
 
-    let sort_query: String = match &params.sort {
-        Some(sort) => {
-            match sort.as_str() {
-                "uploaded_ASC" => "upload_date ASC".to_string(),
-                "uploaded_DESC" => "upload_date DESC".to_string(),
-                "seeders_ASC" => "seeders ASC".to_string(),
-                "seeders_DESC" => "seeders DESC".to_string(),
-                "leechers_ASC" => "leechers ASC".to_string(),
-                "leechers_DESC" => "leechers DESC".to_string(),
-                "name_ASC" => "title ASC".to_string(),
-                "name_DESC" => "title DESC".to_string(),
-                "size_ASC" => "file_size ASC".to_string(),
-                "size_DESC" => "file_size DESC".to_string(),
-                _ => "upload_date DESC".to_string()
-            }
-        }
-        None => "upload_date DESC".to_string()
-    };
+    let (sort, order) = params.sort.as_deref().map(parse_sort).unwrap_or_default();
 
     let category_filter_query = if let Some(c) = categories {
         let mut i = 0;
         let mut category_filters = String::new();
         for category in c.iter() {
             // don't take user input in the db query
-            if let Some(sanitized_category) = &app_data.database.verify_category(category).await {
-                let mut str = format!("tc.name = '{}'", sanitized_category);
+            if let Some(sanitized_category) = app_data.database.get_category_by_name(category).await? {
+                let mut str = if params.include_subcategories.unwrap_or(false) {
+                    let ids = app_data.database.get_category_descendant_ids(sanitized_category.category_id).await?;
+                    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                    format!("tc.category_id IN ({})", ids.join(","))
+                } else {
+                    format!("tc.category_id = {}", sanitized_category.category_id)
+                };
                 if i > 0 { str = format!(" OR {}", str); }
                 category_filters.push_str(&str);
                 i += 1;
             }
         }
         if category_filters.len() > 0 {
-            format!("INNER JOIN torrust_categories tc ON tt.category_id = tc.category_id AND ({})", category_filters)
+            format!("INNER JOIN torrust_categories tc ON torrust_torrents.category_id = tc.category_id AND ({})", category_filters)
         } else {
             String::new()
         }
@@ -117,7 +147,8 @@ pub async fn get_torrents(params: Query<DisplayInfo>, app_data: WebAppData) -> S
         String::new()
     };
 
-    let mut query_string = format!("SELECT tt.* FROM torrust_torrents tt {} WHERE title LIKE ?", category_filter_query);
+    // no alias on torrust_torrents, the sort expressions refer to it by name
+    let mut query_string = format!("SELECT torrust_torrents.* FROM torrust_torrents {} WHERE torrust_torrents.deleted_at IS NULL AND torrust_torrents.status = 'approved' AND title LIKE ?", category_filter_query);
     let count_query_string = format!("SELECT COUNT(torrent_id) as count FROM ({})", query_string);
 
     let count: TorrentCount = sqlx::query_as::<_, TorrentCount>(&count_query_string)
@@ -125,7 +156,8 @@ pub async fn get_torrents(params: Query<DisplayInfo>, app_data: WebAppData) -> S
         .fetch_one(&app_data.database.pool)
         .await?;
 
-    query_string = format!("{} ORDER BY {} LIMIT ?, ?", query_string, sort_query);
+    // featured torrents stay on top whatever the chosen sort
+    query_string = format!("{} ORDER BY featured DESC, {} {} LIMIT ?, ?", query_string, sort.column(), order.as_sql());
 
     let res: Vec<TorrentListing> = sqlx::query_as::<_, TorrentListing>(&query_string)
         .bind(search)
@@ -143,24 +175,244 @@ pub async fn get_torrents(params: Query<DisplayInfo>, app_data: WebAppData) -> S
     }))
 }
 
+// eg: "size_DESC", anything unknown falls back to the newest uploads first
+fn parse_sort(sort: &str) -> (SortField, SortOrder) {
+    let (field, order) = match sort.rsplit_once('_') {
+        Some(v) => v,
+        None => return Default::default()
+    };
+
+    let field = match field {
+        "uploaded" => SortField::UploadDate,
+        "seeders" => SortField::Seeders,
+        "leechers" => SortField::Leechers,
+        "name" => SortField::Title,
+        "size" => SortField::Size,
+        "downloads" => SortField::Downloads,
+        "views" => SortField::Views,
+        "score" => SortField::Score,
+        _ => return Default::default()
+    };
+
+    let order = match order {
+        "ASC" => SortOrder::Asc,
+        "DESC" => SortOrder::Desc,
+        _ => return Default::default()
+    };
+
+    (field, order)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportInfo {
+    format: ExportFormat,
+}
+
+// eg: /torrents/export?format=csv
+pub async fn export_torrents(req: HttpRequest, params: Query<ExportInfo>, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let (content_type, file_name) = match params.format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "torrents.csv"),
+        ExportFormat::Json => ("application/json", "torrents.json"),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .append_header(("Content-Disposition", format!("attachment; filename=\"{}\"", file_name)))
+        .streaming(Box::pin(export_body(app_data.database.clone(), params.format)))
+    )
+}
+
+// runs the export in its own task and streams what it writes. the pipe holds at most
+// `EXPORT_CHUNK_SIZE` bytes, so the export waits whenever the download falls behind.
+// the response has started by the time a row fails, so an error aborts the body instead
+// of ending a truncated export like a complete one
+fn export_body(database: Arc<Database>, format: ExportFormat) -> impl Stream<Item = Result<web::Bytes, ServiceError>> {
+    let (mut writer, reader) = tokio::io::duplex(EXPORT_CHUNK_SIZE);
+    let (result_sender, result_receiver) = oneshot::channel();
+
+    actix_web::rt::spawn(async move {
+        let res = database.export_torrents(format, &mut writer).await;
+
+        if let Err(e) = &res {
+            tracing::warn!(error = %e, "torrent export stopped");
+        }
+
+        // sent before the pipe closes, so the reader knows how the export ended once it sees the end
+        let _ = result_sender.send(res);
+        drop(writer);
+    });
+
+    futures::stream::unfold((reader, Some(result_receiver)), |(mut reader, result_receiver)| async move {
+        let mut chunk = vec![0; EXPORT_CHUNK_SIZE];
+
+        match reader.read(&mut chunk).await {
+            Ok(0) => match result_receiver?.await {
+                Ok(Ok(_)) => None,
+                _ => Some((Err(ServiceError::InternalServerError), (reader, None)))
+            },
+            Ok(read) => {
+                chunk.truncate(read);
+                Some((Ok(web::Bytes::from(chunk)), (reader, result_receiver)))
+            }
+            Err(_) => Some((Err(ServiceError::InternalServerError), (reader, None)))
+        }
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportInfo {
+    format: ExportFormat,
+    // create categories missing from this index instead of skipping their torrents
+    #[serde(default)]
+    create_categories: bool,
+}
+
+// eg: POST /torrents/import?format=json&create_categories=true with the dump as body
+pub async fn import_torrents(req: HttpRequest, params: Query<ImportInfo>, body: web::Bytes, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let report = app_data.database.import_torrents(&body, params.format, params.create_categories).await?;
+
+    app_data.database.log_admin_action(
+        user.user_id,
+        "import_torrents",
+        "torrents",
+        Some(&format!("{} inserted, {} skipped, {} errored", report.inserted, report.skipped, report.errored))
+    ).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: report
+    }))
+}
+
+// server-sent events, one per approved upload, eg:
+// `event: new_torrent\ndata: {"torrent_id":1,"info_hash":"..","title":"..",..}\n\n`
+pub async fn get_live_torrents(app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let receiver = app_data.database.subscribe_new_torrents();
+
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            let event = match tokio::time::timeout(Duration::from_secs(LIVE_KEEPALIVE_SECS), receiver.recv()).await {
+                Err(_) => ": keepalive\n\n".to_string(),
+                Ok(Ok(torrent)) => match serde_json::to_string(&torrent) {
+                    Ok(json) => format!("event: new_torrent\ndata: {}\n\n", json),
+                    Err(_) => continue
+                },
+                // a slow client misses the events it fell behind on, the uploads never wait for it
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    tracing::debug!(skipped, "live torrent subscriber lagged behind");
+                    continue
+                }
+                Ok(Err(RecvError::Closed)) => return None
+            };
+
+            return Some((Ok::<_, actix_web::Error>(web::Bytes::from(event)), receiver))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .append_header(("Cache-Control", "no-cache"))
+        .streaming(Box::pin(events)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RssInfo {
+    category: Option<String>,
+}
+
+pub async fn get_rss_feed(req: HttpRequest, params: Query<RssInfo>, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let torrents = match &params.category {
+        Some(name) => {
+            let category = app_data.database.get_category_by_name(name).await?.ok_or(ServiceError::InvalidCategory)?;
+            app_data.database.get_latest_torrents_in_category(category.category_id, RSS_FEED_SIZE).await?
+        }
+        None => app_data.database.get_latest_torrents(RSS_FEED_SIZE).await?
+    };
+
+    let base_url = get_base_url(&req, &app_data).await;
+
+    let settings = app_data.cfg.settings.read().await;
+
+    let title = match &params.category {
+        Some(name) => format!("{} - {}", settings.website.name, name),
+        None => settings.website.name.clone()
+    };
+
+    drop(settings);
+
+    let channel = Channel {
+        title: &title,
+        link: &base_url,
+        description: "Latest torrents",
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/rss+xml; charset=UTF-8")
+        .body(rss::build_feed(&channel, &base_url, &torrents)))
+}
+
+pub async fn get_sitemap(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let base_url = get_base_url(&req, &app_data).await;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/xml; charset=UTF-8")
+        .body(sitemap::generate_sitemap(&app_data.database, &base_url).await?))
+}
+
+pub async fn get_child_sitemap(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let page = req.match_info().get("page").and_then(|page| page.parse::<i64>().ok()).ok_or(ServiceError::BadRequest)?;
+
+    let base_url = get_base_url(&req, &app_data).await;
+
+    match sitemap::generate_child_sitemap(&app_data.database, &base_url, page).await? {
+        Some(xml) => Ok(HttpResponse::Ok()
+            .content_type("application/xml; charset=UTF-8")
+            .body(xml)),
+        None => Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+// the configured base url, or the one the request came in on
+async fn get_base_url(req: &HttpRequest, app_data: &WebAppData) -> String {
+    match &app_data.cfg.settings.read().await.net.base_url {
+        Some(base_url) => base_url.clone(),
+        None => {
+            let conn_info = req.connection_info();
+            format!("{}://{}", conn_info.scheme(), conn_info.host())
+        }
+    }
+}
+
 pub async fn get_torrent(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
     // optional
     let user = app_data.auth.get_user_from_request(&req).await;
 
-    let settings = app_data.cfg.settings.read().await;
-
     let torrent_id = get_torrent_id_from_request(&req)?;
 
     let torrent_listing = app_data.database.get_torrent_by_id(torrent_id).await?;
     let mut torrent_response = TorrentResponse::from_listing(torrent_listing);
 
-    let filepath = format!("{}/{}", settings.storage.upload_path, torrent_response.torrent_id.to_string() + ".torrent");
-
-    let tracker_url = settings.tracker.url.clone();
+    // count a page once per ip and window so refreshing doesn't inflate the views, the
+    // rate limit table doubles as the record of who has been counted
+    let view_key = format!("view:{}", torrent_id);
+    if app_data.database.check_and_increment_rate_limit(&client_ip(&req), &view_key, 1, VIEW_DEDUP_WINDOW_SECS).await.unwrap_or(false) {
+        let _ = app_data.database.increment_view_count(torrent_id).await;
+    }
 
-    drop(settings);
+    let torrent_file = get_torrent_bytes(&app_data, torrent_id).await
+        .ok()
+        .and_then(|bytes| parse_torrent::decode_torrent(&bytes).ok());
 
-    if let Ok(torrent) = parse_torrent::read_torrent_from_file(&filepath) {
+    if let Some(torrent) = torrent_file {
         // add torrent file/files to response
         if let Some(files) = torrent.info.files {
             torrent_response.files = Some(files);
@@ -183,24 +435,15 @@ pub async fn get_torrent(req: HttpRequest, app_data: WebAppData) -> ServiceResul
         }
     }
 
-    // add self-hosted tracker url
-    if user.is_ok() {
-        let unwrapped_user = user.unwrap();
-        let personal_announce_url = app_data.tracker.get_personal_announce_url(&unwrapped_user).await?;
-        // add personal tracker url to front of vec
-        torrent_response.trackers.insert(0, personal_announce_url);
-    } else {
-        // add tracker to front of vec
-        torrent_response.trackers.insert(0, tracker_url);
+    // our own announce urls go in front of the ones from the torrent file
+    let mut trackers = app_data.tracker.get_announce_urls(user.ok().as_ref()).await?;
+    for tracker in torrent_response.trackers.drain(..) {
+        if !trackers.contains(&tracker) { trackers.push(tracker) }
     }
+    torrent_response.trackers = trackers;
 
-    // add magnet link
-    let mut magnet = format!("magnet:?xt=urn:btih:{}&dn={}", torrent_response.info_hash, urlencoding::encode(&torrent_response.title));
-    // add trackers from torrent file to magnet link
-    for tracker in &torrent_response.trackers {
-        magnet.push_str(&format!("&tr={}", urlencoding::encode(tracker)));
-    }
-    torrent_response.magnet_link = magnet;
+    // add magnet link with the trackers from the torrent file
+    torrent_response.magnet_link = build_magnet(&torrent_response.info_hash, &torrent_response.title, &torrent_response.trackers);
 
     // get realtime seeders and leechers
     if let Ok(torrent_info) = app_data.tracker.get_torrent_info(&torrent_response.info_hash).await {
@@ -257,15 +500,9 @@ pub async fn delete_torrent(req: HttpRequest, app_data: WebAppData) -> ServiceRe
 
     let torrent_id = get_torrent_id_from_request(&req)?;
 
-    let res = sqlx::query!(
-        "DELETE FROM torrust_torrents WHERE torrent_id = ?",
-        torrent_id
-    )
-        .execute(&app_data.database.pool)
-        .await;
+    app_data.database.soft_delete_torrent(torrent_id).await?;
 
-    if let Err(_) = res { return Err(ServiceError::TorrentNotFound) }
-    if res.unwrap().rows_affected() == 0 { return Err(ServiceError::TorrentNotFound) }
+    app_data.database.log_admin_action(user.user_id, "delete_torrent", &torrent_id.to_string(), None).await?;
 
     Ok(HttpResponse::Ok().json(OkResponse {
         data: NewTorrentResponse {
@@ -277,8 +514,14 @@ pub async fn delete_torrent(req: HttpRequest, app_data: WebAppData) -> ServiceRe
 pub async fn upload_torrent(req: HttpRequest, payload: Multipart, app_data: WebAppData) -> ServiceResult<impl Responder> {
     let user = app_data.auth.get_user_from_request(&req).await?;
 
+    let rate_limits = app_data.cfg.settings.read().await.rate_limits.clone();
+    check_rate_limit(&req, &app_data, "upload", rate_limits.upload_max, rate_limits.upload_window_secs).await?;
+
     let mut torrent_request = get_torrent_request_from_payload(payload).await?;
 
+    let title_filter = ContentFilter::from_config(&app_data.cfg.settings.read().await.title_filter);
+    if title_filter.contains_blocked_terms(&torrent_request.fields.title) { return Err(ServiceError::TitleRejected) }
+
     // update announce url to our own tracker url
     torrent_request.torrent.set_torrust_config(&app_data.cfg).await;
 
@@ -294,13 +537,13 @@ pub async fn upload_torrent(req: HttpRequest, payload: Multipart, app_data: WebA
         Err(_) => return Err(ServiceError::InvalidCategory),
     };
 
-    let username = user.username;
-    let info_hash = torrent_request.torrent.info_hash();
-    let title = torrent_request.fields.title;
-    //let category = torrent_request.fields.category;
-    let description = torrent_request.fields.description;
-    //let current_time = current_time() as i64;
-    let file_size = torrent_request.torrent.file_size();
+    // derive the info hash and size from the exact bytes we are going to store
+    let torrent_bytes = parse_torrent::encode_torrent(&torrent_request.torrent).map_err(|_| ServiceError::InvalidTorrentFile)?;
+    let parsed_torrent = bencode::parse_torrent(&torrent_bytes)?;
+
+    app_data.database.check_upload_quota(user.user_id, parsed_torrent.file_size).await?;
+
+    let info_hash = parsed_torrent.info_hash;
     let mut seeders = 0;
     let mut leechers = 0;
 
@@ -309,23 +552,61 @@ pub async fn upload_torrent(req: HttpRequest, payload: Multipart, app_data: WebA
         leechers = torrent_info.leechers;
     }
 
-    let torrent_id = app_data.database.insert_torrent_and_get_id(username, info_hash, title, row.category_id, description, file_size, seeders, leechers).await?;
+    let status = if app_data.cfg.settings.read().await.moderation.auto_approve_uploads {
+        TorrentStatus::Approved
+    } else {
+        TorrentStatus::Pending
+    };
 
-    // whitelist info hash on tracker
-    let _ = app_data.tracker.whitelist_info_hash(torrent_request.torrent.info_hash()).await;
+    let fields = torrent_request.fields;
 
-    let settings = app_data.cfg.settings.read().await;
+    // looked up before the insert, so the new torrent doesn't match itself
+    let similar_torrents = match app_data.database.find_similar_titles(&fields.title, SIMILAR_TITLE_THRESHOLD).await {
+        Ok(similar_torrents) => similar_torrents,
+        Err(e) => {
+            tracing::warn!(error = %e, "unable to look up similar titles");
+            vec![]
+        }
+    };
 
-    let upload_folder = settings.storage.upload_path.clone();
-    let filepath = format!("{}/{}", upload_folder, torrent_id.to_string() + ".torrent");
+    let torrent_id = app_data.database.insert_torrent(NewTorrent {
+        uploader: user.username,
+        info_hash: info_hash.clone(),
+        title: fields.title,
+        category_id: row.category_id,
+        description: fields.description,
+        file_size: parsed_torrent.file_size,
+        seeders,
+        leechers,
+        status,
+        files: parsed_torrent.files,
+        source: fields.source,
+        language: fields.language,
+        resolution: fields.resolution,
+        piece_length: Some(parsed_torrent.piece_length),
+        piece_count: parsed_torrent.piece_count,
+        torrent_file: Some(torrent_bytes),
+    }).await?;
 
-    drop(settings);
+    // whitelist info hash on tracker
+    let _ = app_data.tracker.whitelist_info_hash(info_hash).await;
+
+    // a failed lookup shouldn't fail an upload that has already been stored
+    match app_data.database.enqueue_saved_search_notifications(torrent_id).await {
+        Ok(queued) => if queued > 0 {
+            tracing::info!(torrent_id, queued, "queued saved search notifications");
+        },
+        Err(e) => tracing::warn!(torrent_id, error = %e, "unable to match saved searches"),
+    }
 
-    save_torrent_file(&upload_folder, &filepath, &torrent_request.torrent).await?;
+    if !similar_torrents.is_empty() {
+        tracing::info!(torrent_id, similar = similar_torrents.len(), "new torrent has similar titles");
+    }
 
     Ok(HttpResponse::Ok().json(OkResponse {
-        data: NewTorrentResponse {
-            torrent_id
+        data: UploadTorrentResponse {
+            torrent_id,
+            similar_torrents
         }
     }))
 }
@@ -333,43 +614,19 @@ pub async fn upload_torrent(req: HttpRequest, payload: Multipart, app_data: WebA
 pub async fn download_torrent(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
     let torrent_id = get_torrent_id_from_request(&req)?;
 
-    let settings = app_data.cfg.settings.read().await;
+    // make sure the torrent has not been removed
+    let _ = app_data.database.get_torrent_by_id(torrent_id).await?;
 
     // optional
     let user = app_data.auth.get_user_from_request(&req).await;
 
-    let filepath = format!("{}/{}", settings.storage.upload_path, torrent_id.to_string() + ".torrent");
+    let torrent_bytes = get_torrent_bytes(&app_data, torrent_id).await?;
 
-    let mut torrent = match parse_torrent::read_torrent_from_file(&filepath) {
-        Ok(torrent) => Ok(torrent),
-        Err(e) => {
-            println!("{:?}", e);
-            Err(ServiceError::InternalServerError)
-        }
-    }?;
+    let announce_urls = app_data.tracker.get_announce_urls(user.ok().as_ref()).await?;
 
-    if user.is_ok() {
-        let unwrapped_user = user.unwrap();
-        let personal_announce_url = app_data.tracker.get_personal_announce_url(&unwrapped_user).await?;
-        torrent.announce = Some(personal_announce_url.clone());
-        if let Some(list) = &mut torrent.announce_list {
-            let mut vec = Vec::new();
-            vec.push(personal_announce_url);
-            list.insert(0, vec);
-        }
-    } else {
-        torrent.announce = Some(settings.tracker.url.clone());
-    }
+    let buffer = bencode::rewrite_announce(&torrent_bytes, &announce_urls)?;
 
-    drop(settings);
-
-    let buffer = match parse_torrent::encode_torrent(&torrent) {
-        Ok(v) => Ok(v),
-        Err(e) => {
-            println!("{:?}", e);
-            Err(ServiceError::InternalServerError)
-        }
-    }?;
+    let _ = app_data.database.increment_download_count(torrent_id).await;
 
     Ok(HttpResponse::Ok()
         .content_type("application/x-bittorrent")
@@ -384,26 +641,17 @@ pub async fn download_torrent(req: HttpRequest, app_data: WebAppData) -> Service
 //     }
 // }
 
-async fn save_torrent_file(upload_folder: &str, filepath: &str, torrent: &Torrent) -> Result<(), ServiceError> {
-    let torrent_bytes = match parse_torrent::encode_torrent(torrent) {
-        Ok(v) => Ok(v),
-        Err(_) => Err(ServiceError::InternalServerError)
-    }?;
-
-    // create torrent upload folder if it does not exist
-    async_std::fs::create_dir_all(&upload_folder).await?;
+// torrents uploaded before .torrent files were kept in the database are read from the upload folder
+async fn get_torrent_bytes(app_data: &WebAppData, torrent_id: i64) -> Result<Vec<u8>, ServiceError> {
+    match app_data.database.get_torrent_file(torrent_id).await {
+        Err(ServiceError::TorrentFileMissing) => {
+            let upload_path = app_data.cfg.settings.read().await.storage.upload_path.clone();
+            let filepath = format!("{}/{}", upload_path, torrent_id.to_string() + ".torrent");
 
-    let mut f = match async_std::fs::File::create(&filepath).await {
-        Ok(v) => Ok(v),
-        Err(_) => Err(ServiceError::InternalServerError)
-    }?;
-
-    match AsyncWriteExt::write_all(&mut f, &torrent_bytes.as_slice()).await {
-        Ok(v) => Ok(v),
-        Err(_) => Err(ServiceError::InternalServerError)
-    }?;
-
-    Ok(())
+            async_std::fs::read(&filepath).await.map_err(|_| ServiceError::TorrentFileMissing)
+        }
+        res => res
+    }
 }
 
 fn get_torrent_id_from_request(req: &HttpRequest) -> Result<i64, ServiceError> {
@@ -425,13 +673,16 @@ async fn get_torrent_request_from_payload(mut payload: Multipart) -> Result<Torr
     let mut title = "".to_string();
     let mut description = "".to_string();
     let mut category = "".to_string();
+    let mut source = None;
+    let mut language = None;
+    let mut resolution = None;
 
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_type = field.content_disposition().unwrap();
         let name = content_type.get_name().unwrap();
 
         match name {
-            "title" | "description" | "category" => {
+            "title" | "description" | "category" | "source" | "language" | "resolution" => {
                 let data = field.next().await;
                 if data.is_none() { continue }
                 let wrapped_data = &data.unwrap().unwrap();
@@ -441,6 +692,9 @@ async fn get_torrent_request_from_payload(mut payload: Multipart) -> Result<Torr
                     "title" => { title = parsed_data.to_string() }
                     "description" => { description = parsed_data.to_string() }
                     "category" => { category = parsed_data.to_string() }
+                    "source" => { source = optional_field(parsed_data) }
+                    "language" => { language = optional_field(parsed_data) }
+                    "resolution" => { resolution = optional_field(parsed_data) }
                     _ => {}
                 }
             }
@@ -462,6 +716,9 @@ async fn get_torrent_request_from_payload(mut payload: Multipart) -> Result<Torr
         title,
         description,
         category,
+        source,
+        language,
+        resolution,
     };
 
     fields.verify()?;
@@ -479,3 +736,47 @@ async fn get_torrent_request_from_payload(mut payload: Multipart) -> Result<Torr
         torrent,
     })
 }
+
+// blank form fields count as not set
+fn optional_field(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    if value.is_empty() { None } else { Some(value.to_string()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_body_streams_the_whole_export() {
+        actix_web::rt::System::new().block_on(async {
+            let database = Arc::new(Database::new("sqlite::memory:").await);
+            sqlx::query("INSERT INTO torrust_users (username, email, password) VALUES ('uploader', 'uploader', 'password')")
+                .execute(&database.pool)
+                .await
+                .unwrap();
+
+            // enough rows for the export to fill the pipe more than once
+            for number in 0..2000u32 {
+                database.insert_torrent(NewTorrent {
+                    uploader: "uploader".to_string(),
+                    info_hash: format!("{:040x}", number),
+                    title: format!("torrent {}", number),
+                    category_id: 1,
+                    file_size: 1024,
+                    ..Default::default()
+                }).await.unwrap();
+            }
+
+            let mut expected = vec![];
+            database.export_torrents(ExportFormat::Csv, &mut expected).await.unwrap();
+            assert!(expected.len() > EXPORT_CHUNK_SIZE * 2);
+
+            let chunks: Vec<web::Bytes> = export_body(database, ExportFormat::Csv).try_collect().await.unwrap();
+
+            assert!(chunks.iter().all(|chunk| chunk.len() <= EXPORT_CHUNK_SIZE));
+            assert_eq!(chunks.concat(), expected);
+        });
+    }
+}
@@ -7,13 +7,15 @@ use pbkdf2::{
     },
     Pbkdf2,
 };
-use std::borrow::Cow;
-use crate::errors::{ServiceResult, ServiceError};
+use crate::errors::{map_sqlx_error, ServiceResult, ServiceError};
 use crate::common::WebAppData;
 use jsonwebtoken::{DecodingKey, decode, Validation, Algorithm};
 use crate::models::response::OkResponse;
-use crate::models::response::TokenResponse;
+use crate::models::response::{TokenResponse, UsersResponse};
 use crate::mailer::VerifyClaims;
+use crate::database::DELETED_USER;
+use crate::handlers::check_rate_limit;
+use crate::utils::time::current_time;
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -24,6 +26,20 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
                 .route(web::post().to(login)))
             .service(web::resource("/ban/{user}")
                 .route(web::delete().to(ban_user)))
+            .service(web::resource("/unban/{user}")
+                .route(web::post().to(unban_user)))
+            .service(web::resource("/promote/{user}")
+                .route(web::post().to(promote_user)))
+            .service(web::resource("/demote/{user}")
+                .route(web::post().to(demote_user)))
+            .service(web::resource("/quota/{user}")
+                .route(web::post().to(set_upload_quota)))
+            .service(web::resource("/delete/{user}")
+                .route(web::delete().to(delete_user)))
+            .service(web::resource("/export")
+                .route(web::get().to(export_user_data)))
+            .service(web::resource("/list")
+                .route(web::get().to(list_users)))
             .service(web::resource("/verify/{token}")
                 .route(web::get().to(verify_user)))
     );
@@ -46,6 +62,9 @@ pub struct Login {
 pub async fn register(req: HttpRequest, payload: web::Json<Register>, app_data: WebAppData) -> ServiceResult<impl Responder> {
     let settings = app_data.cfg.settings.read().await;
 
+    let rate_limits = settings.rate_limits.clone();
+    check_rate_limit(&req, &app_data, "register", rate_limits.register_max, rate_limits.register_window_secs).await?;
+
     if payload.password != payload.confirm_password {
         return Err(ServiceError::PasswordsDontMatch);
     }
@@ -70,28 +89,27 @@ pub async fn register(req: HttpRequest, payload: web::Json<Register>, app_data:
         return Err(ServiceError::UsernameInvalid)
     }
 
+    // reserved for the uploads of deleted accounts
+    if payload.username.eq_ignore_ascii_case(DELETED_USER) {
+        return Err(ServiceError::UsernameTaken)
+    }
+
+    let registration_date = current_time() as i64;
+
     let res = sqlx::query!(
-        "INSERT INTO torrust_users (username, email, password) VALUES ($1, $2, $3)",
+        "INSERT INTO torrust_users (username, email, password, registration_date) VALUES ($1, $2, $3, $4)",
         payload.username,
         payload.email,
         password_hash,
+        registration_date,
     )
         .execute(&app_data.database.pool)
         .await;
 
-    if let Err(sqlx::Error::Database(err)) = res {
-        return if err.code() == Some(Cow::from("2067")) {
-            if err.message().contains("torrust_users.username") {
-                Err(ServiceError::UsernameTaken)
-            } else if err.message().contains("torrust_users.email") {
-                Err(ServiceError::EmailTaken)
-            } else {
-                Err(ServiceError::InternalServerError)
-            }
-        } else {
-            Err(sqlx::Error::Database(err).into())
-        };
-    }
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => return Err(map_sqlx_error(e))
+    };
 
     // count accounts
     let res_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM torrust_users")
@@ -116,7 +134,7 @@ pub async fn register(req: HttpRequest, payload: web::Json<Register>, app_data:
             .await;
 
         // get user id from user insert res
-        let user_id = res.unwrap().last_insert_rowid();
+        let user_id = res.last_insert_rowid();
 
         if mail_res.is_err() {
             let _ = app_data.database.delete_user(user_id).await;
@@ -133,13 +151,17 @@ pub async fn login(payload: web::Json<Login>, app_data: WebAppData) -> ServiceRe
     let settings = app_data.cfg.settings.read().await;
 
     let res = if payload.login.contains('@') {
-        app_data.database.get_user_with_email(&payload.login).await
+        app_data.database.get_user_with_email(&payload.login).await?
     } else {
-        app_data.database.get_user_with_username(&payload.login).await
+        app_data.database.get_user_with_username(&payload.login).await?
     };
 
     match res {
         Some(user) => {
+            if user.banned {
+                return Err(ServiceError::UserBanned)
+            }
+
             if settings.mail.email_verification_enabled && !user.email_verified {
                 return Err(ServiceError::EmailNotVerified)
             }
@@ -152,6 +174,8 @@ pub async fn login(payload: web::Json<Login>, app_data: WebAppData) -> ServiceRe
                 return Err(ServiceError::WrongPasswordOrUsername);
             }
 
+            app_data.database.touch_last_login(user.user_id).await?;
+
             let username = user.username.clone();
             let token = app_data.auth.sign_jwt(user.clone()).await;
 
@@ -203,7 +227,12 @@ pub async fn verify_user(req: HttpRequest, app_data: WebAppData) -> String {
     String::from("Email verified, you can close this page.")
 }
 
-pub async fn ban_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BanInfo {
+    pub reason: Option<String>,
+}
+
+pub async fn ban_user(req: HttpRequest, params: web::Query<BanInfo>, app_data: WebAppData) -> ServiceResult<impl Responder> {
     let user = app_data.auth.get_user_from_request(&req).await?;
 
     // check if user is administrator
@@ -211,21 +240,182 @@ pub async fn ban_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<i
 
     let to_be_banned_username = req.match_info().get("user").unwrap();
 
-    let res = sqlx::query!(
-        "DELETE FROM torrust_users WHERE username = ? AND administrator = 0",
-        to_be_banned_username
-    )
-        .execute(&app_data.database.pool)
-        .await;
+    let to_be_banned = match app_data.database.get_user_with_username(to_be_banned_username).await? {
+        // administrators can't be banned
+        Some(v) if !v.administrator => v,
+        _ => return Err(ServiceError::UsernameNotFound)
+    };
+
+    let reason = params.reason.as_deref().unwrap_or("");
 
-    if let Err(_) = res { return Err(ServiceError::UsernameNotFound) }
-    if res.unwrap().rows_affected() == 0 { return Err(ServiceError::UsernameNotFound) }
+    app_data.database.ban_user(to_be_banned.user_id, reason).await?;
+
+    let details = if reason.is_empty() { None } else { Some(reason) };
+    app_data.database.log_admin_action(user.user_id, "ban_user", to_be_banned_username, details).await?;
 
     Ok(HttpResponse::Ok().json(OkResponse {
         data: format!("Banned user: {}", to_be_banned_username)
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct QuotaInfo {
+    // leave out to remove the quota
+    pub bytes: Option<i64>,
+}
+
+pub async fn set_upload_quota(req: HttpRequest, params: web::Query<QuotaInfo>, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let target_username = req.match_info().get("user").unwrap();
+
+    let target = match app_data.database.get_user_with_username(target_username).await? {
+        Some(v) => v,
+        None => return Err(ServiceError::UsernameNotFound)
+    };
+
+    app_data.database.set_upload_quota(target.user_id, params.bytes).await?;
+
+    let details = params.bytes.map(|bytes| bytes.to_string());
+    app_data.database.log_admin_action(user.user_id, "set_upload_quota", target_username, details.as_deref()).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: format!("Set upload quota of user: {}", target_username)
+    }))
+}
+
+pub async fn unban_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let to_be_unbanned_username = req.match_info().get("user").unwrap();
+
+    let to_be_unbanned = match app_data.database.get_user_with_username(to_be_unbanned_username).await? {
+        Some(v) => v,
+        None => return Err(ServiceError::UsernameNotFound)
+    };
+
+    app_data.database.unban_user(to_be_unbanned.user_id).await?;
+
+    app_data.database.log_admin_action(user.user_id, "unban_user", to_be_unbanned_username, None).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: format!("Unbanned user: {}", to_be_unbanned_username)
+    }))
+}
+
+pub async fn promote_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let to_be_promoted_username = req.match_info().get("user").unwrap();
+
+    let to_be_promoted = match app_data.database.get_user_with_username(to_be_promoted_username).await? {
+        Some(v) => v,
+        None => return Err(ServiceError::UsernameNotFound)
+    };
+
+    app_data.database.promote_to_admin(to_be_promoted.user_id, user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: format!("Promoted user: {}", to_be_promoted_username)
+    }))
+}
+
+pub async fn demote_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let to_be_demoted_username = req.match_info().get("user").unwrap();
+
+    let to_be_demoted = match app_data.database.get_user_with_username(to_be_demoted_username).await? {
+        Some(v) => v,
+        None => return Err(ServiceError::UsernameNotFound)
+    };
+
+    app_data.database.demote_from_admin(to_be_demoted.user_id, user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: format!("Demoted user: {}", to_be_demoted_username)
+    }))
+}
+
+pub async fn delete_user(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let to_be_deleted_username = req.match_info().get("user").unwrap();
+
+    let to_be_deleted = match app_data.database.get_user_with_username(to_be_deleted_username).await? {
+        // administrators can't be deleted
+        Some(v) if !v.administrator => v,
+        _ => return Err(ServiceError::UsernameNotFound)
+    };
+
+    let deleted_user_torrents = app_data.cfg.settings.read().await.moderation.deleted_user_torrents;
+
+    app_data.database.delete_user_cascade(to_be_deleted.user_id, deleted_user_torrents).await?;
+
+    app_data.database.log_admin_action(user.user_id, "delete_user", to_be_deleted_username, None).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: format!("Deleted user: {}", to_be_deleted_username)
+    }))
+}
+
+// everything we store about the logged in user, as JSON
+pub async fn export_user_data(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    let export = app_data.database.export_user_data(user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: export
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserListInfo {
+    page_size: Option<i64>,
+    page: Option<i64>,
+    // matches usernames and email addresses
+    search: Option<String>,
+}
+
+// eg: /user/list?search=gmail&page=1
+pub async fn list_users(req: HttpRequest, params: web::Query<UserListInfo>, app_data: WebAppData) -> ServiceResult<impl Responder> {
+    let user = app_data.auth.get_user_from_request(&req).await?;
+
+    // check if user is administrator
+    if !user.administrator { return Err(ServiceError::Unauthorized) }
+
+    let page_size = params.page_size.unwrap_or(30);
+    let offset = params.page.unwrap_or(0) * page_size;
+
+    let (results, total) = match &params.search {
+        Some(search) => app_data.database.search_users(search, page_size, offset).await?,
+        None => app_data.database.get_users_paginated(page_size, offset).await?
+    };
+
+    Ok(HttpResponse::Ok().json(OkResponse {
+        data: UsersResponse {
+            total,
+            results
+        }
+    }))
+}
+
 pub async fn me(req: HttpRequest, app_data: WebAppData) -> ServiceResult<impl Responder> {
     let user = match app_data.auth.get_user_from_request(&req).await {
         Ok(user) => Ok(user),
@@ -7,7 +7,12 @@ pub mod errors;
 pub mod common;
 pub mod auth;
 pub mod tracker;
+pub mod scraper;
 pub mod mailer;
+pub mod feeds;
+pub mod content;
+pub mod scheduler;
+pub mod request_id;
 
 trait AsCSV {
     fn as_csv<T>(&self) -> Result<Option<Vec<T>>, ()>
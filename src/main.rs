@@ -1,16 +1,29 @@
 use std::sync::Arc;
+use std::time::Duration;
 use actix_web::{App, HttpServer, middleware, web};
 use actix_cors::Cors;
-use torrust_index_backend::database::Database;
+use tracing_subscriber::EnvFilter;
+use torrust_index_backend::database::{Database, DatabaseConfig};
 use torrust_index_backend::{handlers};
+use torrust_index_backend::handlers::torrent::VIEW_DEDUP_WINDOW_SECS;
 use torrust_index_backend::config::{Configuration};
 use torrust_index_backend::common::AppData;
 use torrust_index_backend::auth::AuthorizationService;
 use torrust_index_backend::tracker::TrackerService;
 use torrust_index_backend::mailer::MailerService;
+use torrust_index_backend::utils::time::current_time;
+use torrust_index_backend::scheduler::Scheduler;
+use torrust_index_backend::request_id::RequestId;
+
+// torrents whose file list was stored before content hashes existed are hashed this many per cleanup run
+const CONTENT_HASH_BACKFILL_BATCH: i64 = 1000;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
     let cfg = match Configuration::load_from_file().await {
         Ok(config) => Arc::new(config),
         Err(error) => {
@@ -20,7 +33,25 @@ async fn main() -> std::io::Result<()> {
 
     let settings = cfg.settings.read().await;
 
-    let database = Arc::new(Database::new(&settings.database.connect_url).await);
+    let database_config = DatabaseConfig {
+        url: settings.database.connect_url.clone(),
+        max_connections: settings.database.max_connections,
+        min_connections: settings.database.min_connections,
+        acquire_timeout_secs: settings.database.acquire_timeout_secs,
+        journal_mode: settings.database.journal_mode.clone(),
+        busy_timeout_ms: settings.database.busy_timeout_ms,
+        foreign_keys: settings.database.foreign_keys,
+    };
+
+    let database = Arc::new(
+        Database::from_config(&database_config)
+            .await
+            .expect("Unable to connect to the database")
+            .with_tracker_key_lifetime(
+                settings.tracker.token_valid_seconds as i64,
+                settings.tracker.token_renewal_seconds as i64,
+            )
+    );
     let auth = Arc::new(AuthorizationService::new(cfg.clone(), database.clone()));
     let tracker_service = Arc::new(TrackerService::new(cfg.clone(), database.clone()));
     let mailer_service = Arc::new(MailerService::new(cfg.clone()).await);
@@ -34,30 +65,55 @@ async fn main() -> std::io::Result<()> {
         )
     );
 
-    // create/update database tables
-    let _ = sqlx::migrate!().run(&database.pool).await;
-
     // create torrent upload folder
     async_std::fs::create_dir_all(&settings.storage.upload_path).await?;
 
-    let interval = settings.database.torrent_info_update_interval;
-    let weak_tracker_service = std::sync::Arc::downgrade(&tracker_service);
-
-    // repeating task, update all seeders and leechers info
-    tokio::spawn(async move {
-        let interval = std::time::Duration::from_secs(interval);
-        let mut interval = tokio::time::interval(interval);
-        interval.tick().await; // first tick is immediate...
-        loop {
-            interval.tick().await;
-            if let Some(tracker) = weak_tracker_service.upgrade() {
-                let _ = tracker.update_torrents().await;
-            } else {
-                break;
+    let mut scheduler = Scheduler::new(database.clone());
+
+    // update all seeders and leechers info
+    let interval = Duration::from_secs(settings.database.torrent_info_update_interval);
+    let tracker = tracker_service.clone();
+    let shutdown = scheduler.shutdown_signal();
+    scheduler.register("update_torrents", interval, move |_| {
+        let tracker = tracker.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            let _ = tracker.update_torrents(&shutdown).await;
+        }
+    });
+
+    let prune_dead_torrents = settings.database.prune_dead_torrents;
+    let dead_torrent_days = settings.database.dead_torrent_days as i64;
+    let stats_history_keep_days = settings.database.stats_history_keep_days as i64;
+    // keep rate limit windows until the longest one is over, torrent view dedup shares the table
+    let rate_limit_retention = settings.rate_limits.register_window_secs
+        .max(settings.rate_limits.upload_window_secs)
+        .max(VIEW_DEDUP_WINDOW_SECS);
+
+    // remove expired tracker keys, old rate limit windows, old stats snapshots and dead torrents
+    scheduler.register("cleanup", Duration::from_secs(3600), move |database| async move {
+        if let Ok(deleted) = database.delete_expired_tracker_keys().await {
+            tracing::info!("Deleted {} expired tracker keys", deleted);
+        }
+        if let Ok(deleted) = database.delete_old_rate_limits(rate_limit_retention).await {
+            tracing::info!("Deleted {} old rate limit windows", deleted);
+        }
+        if let Ok(deleted) = database.prune_torrent_stats_history(stats_history_keep_days).await {
+            tracing::info!("Deleted {} old torrent stats snapshots", deleted);
+        }
+        if let Ok(hashed) = database.backfill_content_hashes(CONTENT_HASH_BACKFILL_BATCH).await {
+            if hashed > 0 { tracing::info!("Hashed the file lists of {} torrents", hashed) }
+        }
+        if prune_dead_torrents {
+            let dead_since = current_time() as i64 - dead_torrent_days * 86_400;
+            if let Ok(deleted) = database.soft_delete_dead_torrents(dead_since).await {
+                tracing::info!("Deleted {} dead torrents", deleted);
             }
         }
     });
 
+    let scheduler = scheduler.start();
+
     let port = settings.net.port;
 
     drop(settings);
@@ -69,9 +125,17 @@ async fn main() -> std::io::Result<()> {
             .wrap(Cors::permissive())
             .app_data(web::Data::new(app_data.clone()))
             .wrap(middleware::Logger::default())
+            .wrap(RequestId)
             .configure(handlers::init_routes)
     })
         .bind(("0.0.0.0", port))?
         .run()
-        .await
+        .await?;
+
+    // the server returns once SIGTERM / ctrl-c came in and the in-flight requests finished.
+    // jobs still use the pool, so stop them before closing it
+    scheduler.shutdown().await;
+    database.close().await;
+
+    Ok(())
 }
@@ -0,0 +1,11 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub audit_log_id: i64,
+    pub admin_user_id: i64,
+    pub action: String,
+    pub target: String,
+    pub details: Option<String>,
+    pub created_at: i64,
+}
@@ -0,0 +1,9 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentView {
+    pub comment_id: i64,
+    pub username: String,
+    pub body: String,
+    pub created_at: i64,
+}
@@ -3,3 +3,6 @@ pub mod torrent;
 pub mod torrent_file;
 pub mod response;
 pub mod tracker_key;
+pub mod comment;
+pub mod report;
+pub mod audit_log;
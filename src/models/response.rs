@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use crate::database::TorrentCompact;
 use crate::models::torrent::TorrentListing;
 use crate::models::torrent_file::File;
+use crate::models::user::UserProfile;
 
 pub enum OkResponses {
     TokenResponse(TokenResponse)
@@ -28,6 +30,13 @@ pub struct NewTorrentResponse {
     pub torrent_id: i64,
 }
 
+// `similar_torrents` warns about a possible re-upload, the upload itself went through
+#[derive(Serialize, Debug)]
+pub struct UploadTorrentResponse {
+    pub torrent_id: i64,
+    pub similar_torrents: Vec<TorrentCompact>,
+}
+
 #[derive(Serialize, Deserialize, Debug, sqlx::FromRow)]
 pub struct CategoryResponse {
     pub name: String,
@@ -42,10 +51,16 @@ pub struct TorrentResponse {
     pub title: String,
     pub description: Option<String>,
     pub category_id: i64,
+    #[serde(with = "crate::utils::time::serde_epoch_as_rfc3339")]
     pub upload_date: i64,
     pub file_size: i64,
     pub seeders: i64,
     pub leechers: i64,
+    pub downloads: i64,
+    pub views: i64,
+    pub piece_length: Option<i64>,
+    pub piece_count: Option<i64>,
+    pub status: String,
     pub files: Option<Vec<File>>,
     pub trackers: Vec<String>,
     pub magnet_link: String,
@@ -64,6 +79,11 @@ impl TorrentResponse {
             file_size: torrent_listing.file_size,
             seeders: torrent_listing.seeders,
             leechers: torrent_listing.leechers,
+            downloads: torrent_listing.downloads,
+            views: torrent_listing.views,
+            piece_length: torrent_listing.piece_length,
+            piece_count: torrent_listing.piece_count,
+            status: torrent_listing.status,
             files: None,
             trackers: vec![],
             magnet_link: "".to_string(),
@@ -76,3 +96,15 @@ pub struct TorrentsResponse {
     pub total: u32,
     pub results: Vec<TorrentListing>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UsersResponse {
+    pub total: i64,
+    pub results: Vec<UserProfile>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PaginatedResponse<T> {
+    pub total: i64,
+    pub results: Vec<T>,
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use crate::models::torrent_file::Torrent;
 use crate::handlers::torrent::CreateTorrent;
 use sqlx::{FromRow};
+use std::str::FromStr;
 
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -12,10 +13,274 @@ pub struct TorrentListing {
     pub title: String,
     pub description: Option<String>,
     pub category_id: i64,
+    #[serde(with = "crate::utils::time::serde_epoch_as_rfc3339")]
     pub upload_date: i64,
     pub file_size: i64,
     pub seeders: i64,
     pub leechers: i64,
+    pub deleted_at: Option<i64>,
+    pub downloads: i64,
+    pub status: String,
+    // failed scrapes in a row, reset on every successful one
+    pub scrape_failures: i64,
+    pub last_seen_seeders_at: Option<i64>,
+    // pinned by a moderator, featured torrents are listed first
+    pub featured: bool,
+    // downloads of freeleech torrents don't count against a user's ratio
+    pub freeleech: bool,
+    pub freeleech_until: Option<i64>,
+    pub source: Option<String>,
+    pub language: Option<String>,
+    pub resolution: Option<String>,
+    // page views, compared with downloads it shows torrents many look at but few grab
+    pub views: i64,
+    pub piece_length: Option<i64>,
+    // v1 pieces, `None` for v2 only torrents
+    pub piece_count: Option<i64>,
+}
+
+// a torrent as written by `Database::export_torrents`, including pending and deleted ones
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTorrent {
+    pub torrent_id: i64,
+    pub info_hash: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub uploader: String,
+    pub upload_date: i64,
+    pub file_size: i64,
+    pub seeders: i64,
+    pub leechers: i64,
+    pub downloads: i64,
+    pub status: String,
+    pub deleted_at: Option<i64>,
+    pub source: Option<String>,
+    pub language: Option<String>,
+    pub resolution: Option<String>,
+}
+
+impl ExportedTorrent {
+    pub const CSV_HEADER: [&'static str; 17] = [
+        "torrent_id", "info_hash", "title", "description", "category", "tags", "uploader", "upload_date", "file_size",
+        "seeders", "leechers", "downloads", "status", "deleted_at", "source", "language", "resolution",
+    ];
+
+    // tags are joined with commas into one field, empty fields stand for missing values
+    pub fn to_csv_record(&self) -> Vec<String> {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+
+        vec![
+            self.torrent_id.to_string(),
+            self.info_hash.clone(),
+            self.title.clone(),
+            optional(&self.description),
+            optional(&self.category),
+            self.tags.join(","),
+            self.uploader.clone(),
+            self.upload_date.to_string(),
+            self.file_size.to_string(),
+            self.seeders.to_string(),
+            self.leechers.to_string(),
+            self.downloads.to_string(),
+            self.status.clone(),
+            self.deleted_at.map(|v| v.to_string()).unwrap_or_default(),
+            optional(&self.source),
+            optional(&self.language),
+            optional(&self.resolution),
+        ]
+    }
+
+    // the reverse of `to_csv_record`, columns are found by their name in `header`
+    pub fn from_csv_record(header: &[String], record: &[String]) -> Result<ExportedTorrent, String> {
+        let field = |name: &str| -> Result<&str, String> {
+            header.iter()
+                .position(|column| column == name)
+                .and_then(|index| record.get(index))
+                .map(|value| value.as_str())
+                .ok_or(format!("missing {}", name))
+        };
+        let optional = |name: &str| -> Result<Option<String>, String> {
+            Ok(Some(field(name)?.to_string()).filter(|value| !value.is_empty()))
+        };
+        let number = |name: &str| -> Result<i64, String> {
+            field(name)?.parse().map_err(|_| format!("invalid {}", name))
+        };
+
+        Ok(ExportedTorrent {
+            torrent_id: number("torrent_id")?,
+            info_hash: field("info_hash")?.to_string(),
+            title: field("title")?.to_string(),
+            description: optional("description")?,
+            category: optional("category")?,
+            tags: field("tags")?.split(',').filter(|tag| !tag.is_empty()).map(|tag| tag.to_string()).collect(),
+            uploader: field("uploader")?.to_string(),
+            upload_date: number("upload_date")?,
+            file_size: number("file_size")?,
+            seeders: number("seeders")?,
+            leechers: number("leechers")?,
+            downloads: number("downloads")?,
+            status: field("status")?.to_string(),
+            deleted_at: optional("deleted_at")?.map(|v| v.parse().map_err(|_| "invalid deleted_at".to_string())).transpose()?,
+            source: optional("source")?,
+            language: optional("language")?,
+            resolution: optional("resolution")?,
+        })
+    }
+}
+
+// a torrent as it is handed to `Database::insert_torrent`, eg:
+// `NewTorrent { uploader, info_hash, title, category_id, ..Default::default() }`
+#[derive(Debug, Clone, Default)]
+pub struct NewTorrent {
+    pub uploader: String,
+    pub info_hash: String,
+    pub title: String,
+    pub category_id: i64,
+    pub description: String,
+    pub file_size: i64,
+    pub seeders: i64,
+    pub leechers: i64,
+    pub status: TorrentStatus,
+    // (path, length) of every file in the torrent
+    pub files: Vec<(String, i64)>,
+    pub source: Option<String>,
+    pub language: Option<String>,
+    pub resolution: Option<String>,
+    pub piece_length: Option<i64>,
+    pub piece_count: Option<i64>,
+    // the .torrent file itself, stored in the same transaction as the torrent
+    pub torrent_file: Option<Vec<u8>>,
+}
+
+// a listing with its category name joined in, the name is `None` if the category no longer exists.
+// serialized with an extra `file_size_human` field, eg: "1.40 GiB"
+#[derive(Debug, Deserialize, FromRow)]
+pub struct TorrentListingView {
+    pub torrent_id: i64,
+    pub uploader: String,
+    pub info_hash: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category_id: i64,
+    pub category_name: Option<String>,
+    #[serde(with = "crate::utils::time::serde_epoch_as_rfc3339")]
+    pub upload_date: i64,
+    pub file_size: i64,
+    pub seeders: i64,
+    pub leechers: i64,
+    pub deleted_at: Option<i64>,
+    pub downloads: i64,
+    pub status: String,
+}
+
+impl Serialize for TorrentListingView {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+
+        let upload_date = crate::utils::time::epoch_to_rfc3339(self.upload_date)
+            .ok_or_else(|| S::Error::custom("timestamp out of range"))?;
+
+        let mut state = serializer.serialize_struct("TorrentListingView", 15)?;
+        state.serialize_field("torrent_id", &self.torrent_id)?;
+        state.serialize_field("uploader", &self.uploader)?;
+        state.serialize_field("info_hash", &self.info_hash)?;
+        state.serialize_field("title", &self.title)?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("category_id", &self.category_id)?;
+        state.serialize_field("category_name", &self.category_name)?;
+        state.serialize_field("upload_date", &upload_date)?;
+        state.serialize_field("file_size", &self.file_size)?;
+        state.serialize_field("file_size_human", &crate::utils::size::format_bytes(self.file_size))?;
+        state.serialize_field("seeders", &self.seeders)?;
+        state.serialize_field("leechers", &self.leechers)?;
+        state.serialize_field("deleted_at", &self.deleted_at)?;
+        state.serialize_field("downloads", &self.downloads)?;
+        state.serialize_field("status", &self.status)?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TorrentStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl TorrentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TorrentStatus::Pending => "pending",
+            TorrentStatus::Approved => "approved",
+            TorrentStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl FromStr for TorrentStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TorrentStatus::Pending),
+            "approved" => Ok(TorrentStatus::Approved),
+            "rejected" => Ok(TorrentStatus::Rejected),
+            _ => Err(())
+        }
+    }
+}
+
+// what a listing can be ordered by, mapped to fixed columns or expressions so user input never
+// reaches the query. the expressions expect `torrust_torrents` to be queried without an alias
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    UploadDate,
+    Seeders,
+    Leechers,
+    Size,
+    Downloads,
+    Title,
+    Views,
+    // sum of the up and down votes
+    Score,
+}
+
+impl SortField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            SortField::UploadDate => "upload_date",
+            SortField::Seeders => "seeders",
+            SortField::Leechers => "leechers",
+            SortField::Size => "file_size",
+            SortField::Downloads => "downloads",
+            SortField::Title => "title",
+            SortField::Views => "views",
+            SortField::Score => "(SELECT COALESCE(SUM(tv.value), 0) FROM torrust_votes tv WHERE tv.torrent_id = torrust_torrents.torrent_id)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
 }
 
 #[derive(Debug)]
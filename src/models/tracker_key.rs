@@ -3,5 +3,20 @@ use serde::{Serialize, Deserialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TrackerKey {
     pub key: String,
+    // the tracker api sends unix seconds, only what we send out is RFC 3339
+    #[serde(serialize_with = "crate::utils::time::serde_epoch_as_rfc3339::serialize")]
     pub valid_until: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_api_keys_deserialize_with_an_integer_expiry() {
+        let tracker_key: TrackerKey = serde_json::from_str(r#"{"key":"abc","valid_until":1650000000}"#).unwrap();
+
+        assert_eq!(tracker_key.valid_until, 1650000000);
+        assert_eq!(serde_json::to_value(&tracker_key).unwrap()["valid_until"], "2022-04-15T05:20:00Z");
+    }
+}
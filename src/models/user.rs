@@ -1,4 +1,7 @@
 use serde::{Serialize, Deserialize};
+use crate::models::torrent::TorrentListing;
+use crate::models::tracker_key::TrackerKey;
+use crate::database::SavedSearch;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
@@ -8,6 +11,12 @@ pub struct User {
     pub email_verified: bool,
     pub password: String,
     pub administrator: bool,
+    pub banned: bool,
+    pub ban_reason: Option<String>,
+    pub registration_date: i64,
+    pub last_login: Option<i64>,
+    // bytes of approved uploads allowed, `None` is unlimited
+    pub upload_quota: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -16,3 +25,68 @@ pub struct Claims {
     pub admin: bool,
     pub exp: u64, // epoch in seconds
 }
+
+// a user without the password hash
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserProfile {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub administrator: bool,
+    pub banned: bool,
+    pub ban_reason: Option<String>,
+    #[serde(with = "crate::utils::time::serde_epoch_as_rfc3339")]
+    pub registration_date: i64,
+    #[serde(default, with = "crate::utils::time::serde_option_epoch_as_rfc3339")]
+    pub last_login: Option<i64>,
+}
+
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        UserProfile {
+            user_id: user.user_id,
+            username: user.username,
+            email: user.email,
+            email_verified: user.email_verified,
+            administrator: user.administrator,
+            banned: user.banned,
+            ban_reason: user.ban_reason,
+            registration_date: user.registration_date,
+            last_login: user.last_login,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserComment {
+    pub comment_id: i64,
+    pub torrent_id: i64,
+    pub body: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserVote {
+    pub torrent_id: i64,
+    pub value: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserBookmark {
+    pub torrent_id: i64,
+    pub created_at: i64,
+}
+
+// everything stored about one user, for data export requests
+#[derive(Debug, Serialize)]
+pub struct UserDataExport {
+    pub profile: UserProfile,
+    pub tracker_keys: Vec<TrackerKey>,
+    // including pending, rejected and deleted uploads
+    pub torrents: Vec<TorrentListing>,
+    pub comments: Vec<UserComment>,
+    pub votes: Vec<UserVote>,
+    pub bookmarks: Vec<UserBookmark>,
+    pub saved_searches: Vec<SavedSearch>,
+}
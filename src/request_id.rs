@@ -0,0 +1,134 @@
+use std::future::{ready, Ready};
+use actix_web::Error;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use futures::future::LocalBoxFuture;
+use tracing::Instrument;
+use crate::utils::token::generate_request_id;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// id of the request being handled, `None` outside of one
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+// gives every request an id and handles it inside a `request` span carrying that id, so all
+// events logged while handling it, database queries included, can be grouped.
+// the id is returned in the `x-request-id` header and in error responses
+pub struct RequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware { service }))
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = generate_request_id();
+        let span = tracing::info_span!("request", request_id = %request_id, method = %req.method(), path = %req.path());
+
+        let fut = span.in_scope(|| self.service.call(req));
+
+        // handler errors are turned into responses further down, inside the scope,
+        // which is how `ServiceError` finds the id
+        Box::pin(REQUEST_ID.scope(request_id.clone(), async move {
+            let mut res = fut.await?;
+
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+
+            Ok(res)
+        }.instrument(span)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use actix_web::{test, web, App, HttpResponse};
+    use crate::errors::ServiceError;
+    use super::*;
+
+    // collects everything the fmt subscriber writes
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn failing_handler() -> Result<HttpResponse, ServiceError> {
+        tracing::info!("handling request");
+        Err(ServiceError::BadRequest)
+    }
+
+    #[test]
+    fn request_id_is_in_events_header_and_error_response() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        actix_web::rt::System::new().block_on(async {
+            let app = test::init_service(
+                App::new()
+                    .wrap(RequestId)
+                    .route("/", web::get().to(failing_handler))
+            ).await;
+
+            let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+
+            let request_id = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(body["request_id"], request_id.as_str());
+
+            let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+            let event = logs.lines().find(|line| line.contains("handling request")).unwrap();
+            assert!(event.contains(&format!("request_id={}", request_id)), "{}", event);
+        });
+    }
+}
@@ -0,0 +1,139 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::future::{join_all, BoxFuture};
+use rand_core::{OsRng, RngCore};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use crate::database::Database;
+
+// every run is delayed by up to this fraction of the interval, so jobs that share an
+// interval don't all hit the database at the same moment
+const JITTER_FRACTION: f64 = 0.1;
+
+type Job = Arc<dyn Fn(Arc<Database>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+struct ScheduledJob {
+    name: &'static str,
+    interval: Duration,
+    job: Job,
+}
+
+// owns the periodic maintenance jobs, eg:
+// `scheduler.register("cleanup", Duration::from_secs(3600), |db| async move { ... })`
+pub struct Scheduler {
+    database: Arc<Database>,
+    jobs: Vec<ScheduledJob>,
+    shutdown_sender: watch::Sender<bool>,
+    shutdown_receiver: watch::Receiver<bool>,
+}
+
+impl Scheduler {
+    pub fn new(database: Arc<Database>) -> Scheduler {
+        let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+
+        Scheduler {
+            database,
+            jobs: vec![],
+            shutdown_sender,
+            shutdown_receiver,
+        }
+    }
+
+    // flips to true on shutdown, long running jobs can check it to stop at a safe point
+    // instead of holding the shutdown up
+    pub fn shutdown_signal(&self) -> watch::Receiver<bool> {
+        self.shutdown_receiver.clone()
+    }
+
+    // the first run happens one interval after `start`
+    pub fn register<F, Fut>(&mut self, name: &'static str, interval: Duration, job: F)
+        where
+            F: Fn(Arc<Database>) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.jobs.push(ScheduledJob {
+            name,
+            interval,
+            job: Arc::new(move |database| Box::pin(job(database))),
+        });
+    }
+
+    pub fn start(self) -> SchedulerHandle {
+        let tasks = self.jobs.into_iter()
+            .map(|job| tokio::spawn(run_job(job, self.database.clone(), self.shutdown_receiver.clone())))
+            .collect();
+
+        SchedulerHandle {
+            shutdown_sender: self.shutdown_sender,
+            tasks,
+        }
+    }
+}
+
+pub struct SchedulerHandle {
+    shutdown_sender: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl SchedulerHandle {
+    // stops scheduling new runs and waits for the ones in progress to finish
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_sender.send(true);
+        join_all(self.tasks).await;
+    }
+}
+
+async fn run_job(job: ScheduledJob, database: Arc<Database>, mut shutdown: watch::Receiver<bool>) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(job.interval + jitter(job.interval)) => {}
+            _ = shutdown.changed() => break,
+        }
+
+        tracing::info!(job = job.name, "scheduled job started");
+        let started = Instant::now();
+
+        (job.job)(database.clone()).await;
+
+        tracing::info!(job = job.name, duration_ms = started.elapsed().as_millis() as u64, "scheduled job finished");
+    }
+}
+
+fn jitter(interval: Duration) -> Duration {
+    let fraction = OsRng.next_u32() as f64 / u32::MAX as f64;
+    interval.mul_f64(fraction * JITTER_FRACTION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn registered_job_runs_until_shutdown() {
+        let database = Arc::new(Database::new("sqlite::memory:").await);
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let mut scheduler = Scheduler::new(database);
+        let job_runs = runs.clone();
+        scheduler.register("count", Duration::from_millis(10), move |_| {
+            let job_runs = job_runs.clone();
+            async move { job_runs.fetch_add(1, Ordering::SeqCst); }
+        });
+        let handle = scheduler.start();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while runs.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("job never ran");
+
+        handle.shutdown().await;
+
+        // no runs are started after shutdown returns
+        let runs_at_shutdown = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), runs_at_shutdown);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use futures::future::join_all;
+use serde_bencode::value::Value;
+use tokio::sync::Semaphore;
+use crate::errors::ServiceError;
+
+#[derive(Debug, Clone)]
+pub struct ScrapeResult {
+    pub info_hash: String,
+    pub seeders: i64,
+    pub leechers: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct ScrapeReport {
+    pub results: Vec<ScrapeResult>,
+    // info hashes that could not be scraped, even after retrying
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+}
+
+impl RetryPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.backoff_base
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.backoff_max)
+    }
+}
+
+// runs `f` with a timeout, timeouts and connection errors are retried with an exponential backoff
+pub async fn retry_with_backoff<T, F, Fut>(policy: &RetryPolicy, timeout: Duration, f: F) -> Result<T, ServiceError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ServiceError>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        let res = match tokio::time::timeout(timeout, f()).await {
+            Ok(res) => res,
+            Err(_) => Err(ServiceError::TrackerOffline)
+        };
+
+        match res {
+            Err(ServiceError::TrackerOffline | ServiceError::InternalServerError) if attempt < policy.retries => {
+                tokio::time::sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+            }
+            res => return res
+        }
+    }
+}
+
+// runs `scrape` for every info hash with at most `concurrency` requests in flight,
+// failed scrapes are logged and reported back in `ScrapeReport::failed`
+pub async fn scrape_concurrently<F, Fut>(info_hashes: Vec<String>, concurrency: usize, timeout: Duration, retry_policy: &RetryPolicy, scrape: F) -> ScrapeReport
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<ScrapeResult, ServiceError>>,
+{
+    let semaphore = Semaphore::new(concurrency.max(1));
+
+    let scrapes = info_hashes.into_iter().map(|info_hash| {
+        let semaphore = &semaphore;
+        let scrape = &scrape;
+
+        async move {
+            // the semaphore is never closed, so acquiring can't fail
+            let _permit = semaphore.acquire().await;
+
+            match retry_with_backoff(retry_policy, timeout, || scrape(info_hash.clone())).await {
+                Ok(result) => Ok(result),
+                Err(e) => {
+                    tracing::warn!(%info_hash, error = %e, "scrape failed");
+                    Err(info_hash)
+                }
+            }
+        }
+    });
+
+    let mut report = ScrapeReport::default();
+
+    for res in join_all(scrapes).await {
+        match res {
+            Ok(result) => report.results.push(result),
+            Err(info_hash) => report.failed.push(info_hash)
+        }
+    }
+
+    report
+}
+
+// scrapes several info hashes in one request from a BEP 48 http scrape url. hashes missing from
+// the response are reported as failed, so they keep their current numbers instead of dropping to 0
+pub async fn http_scrape(client: &reqwest::Client, scrape_url: &str, info_hashes: &[String]) -> Result<ScrapeReport, ServiceError> {
+    let mut request_url = scrape_url.to_string();
+    let mut binary_hashes = Vec::with_capacity(info_hashes.len());
+
+    for (i, info_hash) in info_hashes.iter().enumerate() {
+        let binary_hash = info_hash_to_bytes(info_hash)?;
+
+        request_url.push(if i == 0 && !scrape_url.contains('?') { '?' } else { '&' });
+        request_url.push_str("info_hash=");
+        request_url.push_str(&urlencoding::encode_binary(&binary_hash));
+
+        binary_hashes.push(binary_hash);
+    }
+
+    let response = match client.get(request_url).send().await {
+        Ok(v) => v,
+        Err(_) => return Err(ServiceError::TrackerOffline)
+    };
+
+    let body = match response.bytes().await {
+        Ok(v) => v,
+        Err(_) => return Err(ServiceError::TrackerOffline)
+    };
+
+    let files = parse_scrape_response(&body)?;
+
+    Ok(scrape_report(info_hashes, &binary_hashes, &files))
+}
+
+fn scrape_report(info_hashes: &[String], binary_hashes: &[Vec<u8>], files: &HashMap<Vec<u8>, (i64, i64)>) -> ScrapeReport {
+    let mut report = ScrapeReport::default();
+
+    for (info_hash, binary_hash) in info_hashes.iter().zip(binary_hashes) {
+        match files.get(binary_hash) {
+            Some(&(seeders, leechers)) => report.results.push(ScrapeResult {
+                info_hash: info_hash.clone(),
+                seeders,
+                leechers,
+            }),
+            None => report.failed.push(info_hash.clone())
+        }
+    }
+
+    report
+}
+
+// hex info hash to the 20 bytes used on the wire, v2 hashes are truncated as described in BEP 52
+fn info_hash_to_bytes(info_hash: &str) -> Result<Vec<u8>, ServiceError> {
+    let mut buffer = [0u8; 32];
+
+    let bytes = match binascii::hex2bin(info_hash.as_bytes(), &mut buffer) {
+        Ok(bytes) if bytes.len() >= 20 => bytes,
+        _ => return Err(ServiceError::InvalidInfoHash)
+    };
+
+    Ok(bytes[..20].to_vec())
+}
+
+// reads `complete` and `incomplete` for every entry in the `files` dictionary
+fn parse_scrape_response(body: &[u8]) -> Result<HashMap<Vec<u8>, (i64, i64)>, ServiceError> {
+    let files = match serde_bencode::from_bytes::<Value>(body) {
+        Ok(Value::Dict(mut response)) => match response.remove(&b"files"[..]) {
+            Some(Value::Dict(files)) => files,
+            _ => return Err(ServiceError::TrackerOffline)
+        },
+        _ => return Err(ServiceError::TrackerOffline)
+    };
+
+    let get_int = |file: &HashMap<Vec<u8>, Value>, key: &[u8]| match file.get(key) {
+        Some(Value::Int(v)) => *v,
+        _ => 0
+    };
+
+    Ok(files.into_iter().filter_map(|(info_hash, file)| match file {
+        Value::Dict(file) => Some((info_hash, (get_int(&file, b"complete"), get_int(&file, b"incomplete")))),
+        _ => None
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_missing_from_the_scrape_response_are_failures() {
+        let known = "a".repeat(40);
+        let unknown = "b".repeat(40);
+        let info_hashes = vec![known.clone(), unknown.clone()];
+        let binary_hashes: Vec<Vec<u8>> = info_hashes.iter().map(|info_hash| info_hash_to_bytes(info_hash).unwrap()).collect();
+
+        let mut body = b"d5:filesd20:".to_vec();
+        body.extend_from_slice(&binary_hashes[0]);
+        body.extend_from_slice(b"d8:completei5e10:incompletei2eeee");
+        let files = parse_scrape_response(&body).unwrap();
+
+        let report = scrape_report(&info_hashes, &binary_hashes, &files);
+
+        assert_eq!(report.results.len(), 1);
+        assert_eq!((report.results[0].info_hash.as_str(), report.results[0].seeders, report.results[0].leechers), (known.as_str(), 5, 2));
+        assert_eq!(report.failed, vec![unknown]);
+    }
+}
@@ -5,6 +5,10 @@ use crate::models::tracker_key::TrackerKey;
 use crate::errors::ServiceError;
 use crate::models::user::User;
 use serde::{Serialize, Deserialize};
+use std::time::Duration;
+use tokio::sync::watch;
+use crate::utils::magnet::build_magnet;
+use crate::scraper::{http_scrape, retry_with_backoff, scrape_concurrently, RetryPolicy, ScrapeReport, ScrapeResult};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TorrentInfo {
@@ -32,6 +36,21 @@ pub struct PeerId {
     pub client: Option<String>
 }
 
+pub fn build_personal_announce(tracker_url: &str, key: &str) -> String {
+    format!("{}/{}", tracker_url.trim_end_matches('/'), key)
+}
+
+// `primary` first, then the mirrors in their configured order, without duplicates
+pub fn announce_urls(primary: String, mirrors: &[String]) -> Vec<String> {
+    let mut urls = vec![primary];
+
+    for mirror in mirrors {
+        if !urls.contains(mirror) { urls.push(mirror.clone()) }
+    }
+
+    urls
+}
+
 pub struct TrackerService {
     cfg: Arc<Configuration>,
     database: Arc<Database>,
@@ -73,16 +92,39 @@ impl TrackerService {
         let tracker_key = self.database.get_valid_tracker_key(user.user_id).await;
 
         match tracker_key {
-            Some(v) => { Ok(format!("{}/{}", settings.tracker.url, v.key)) }
+            Some(v) => { Ok(build_personal_announce(&settings.tracker.url, &v.key)) }
             None => {
                 match self.retrieve_new_tracker_key(user.user_id).await {
-                    Ok(v) => { Ok(format!("{}/{}", settings.tracker.url, v.key)) },
+                    Ok(v) => { Ok(build_personal_announce(&settings.tracker.url, &v.key)) },
                     Err(_) => { Err(ServiceError::TrackerOffline) }
                 }
             }
         }
     }
 
+    // the personal announce url of `user`, or the public one for guests, followed by the mirrors
+    pub async fn get_announce_urls(&self, user: Option<&User>) -> Result<Vec<String>, ServiceError> {
+        let settings = self.cfg.settings.read().await;
+        let tracker_url = settings.tracker.url.clone();
+        let mirrors = settings.tracker.announce_urls.clone();
+        drop(settings);
+
+        let primary = match user {
+            Some(user) => self.get_personal_announce_url(user).await?,
+            None => tracker_url
+        };
+
+        Ok(announce_urls(primary, &mirrors))
+    }
+
+    pub async fn get_torrent_magnet(&self, torrent_id: i64) -> Result<String, ServiceError> {
+        let torrent = self.database.get_torrent_by_id(torrent_id).await?;
+
+        let trackers = self.get_announce_urls(None).await?;
+
+        Ok(build_magnet(&torrent.info_hash, &torrent.title, &trackers))
+    }
+
     pub async fn retrieve_new_tracker_key(&self, user_id: i64) -> Result<TrackerKey, ServiceError> {
         let settings = self.cfg.settings.read().await;
 
@@ -111,8 +153,23 @@ impl TrackerService {
         Ok(tracker_key)
     }
 
-    // get torrent info from tracker api
+    // get torrent info from tracker api and store the seeders and leechers
     pub async fn get_torrent_info(&self, info_hash: &str) -> Result<TorrentInfo, ServiceError> {
+        match self.fetch_torrent_info(info_hash).await {
+            Ok(torrent_info) => {
+                let _ = self.database.update_tracker_info(info_hash, torrent_info.seeders, torrent_info.leechers).await;
+                Ok(torrent_info)
+            },
+            Err(ServiceError::TorrentNotFound) => {
+                let _ = self.database.update_tracker_info(info_hash, 0, 0).await;
+                Err(ServiceError::TorrentNotFound)
+            },
+            Err(e) => Err(e)
+        }
+    }
+
+    // get torrent info from tracker api without touching the database
+    async fn fetch_torrent_info(&self, info_hash: &str) -> Result<TorrentInfo, ServiceError> {
         let settings = self.cfg.settings.read().await;
 
         let request_url =
@@ -128,27 +185,94 @@ impl TrackerService {
             Err(_) => Err(ServiceError::InternalServerError)
         }?;
 
-        let torrent_info = match response.json::<TorrentInfo>().await {
-            Ok(torrent_info) => {
-                let _ = self.database.update_tracker_info(info_hash, torrent_info.seeders, torrent_info.leechers).await;
-                Ok(torrent_info)
-            },
+        match response.json::<TorrentInfo>().await {
+            Ok(torrent_info) => Ok(torrent_info),
             Err(e) => {
                 eprintln!("{:?}", e);
-                let _ = self.database.update_tracker_info(info_hash, 0, 0).await;
                 Err(ServiceError::TorrentNotFound)
             }
-        }?;
+        }
+    }
 
-        Ok(torrent_info)
+    async fn scrape_torrent(&self, info_hash: String) -> Result<ScrapeResult, ServiceError> {
+        match self.fetch_torrent_info(&info_hash).await {
+            Ok(torrent_info) => Ok(ScrapeResult { info_hash, seeders: torrent_info.seeders, leechers: torrent_info.leechers }),
+            // tracker does not know this torrent
+            Err(ServiceError::TorrentNotFound) => Ok(ScrapeResult { info_hash, seeders: 0, leechers: 0 }),
+            Err(e) => Err(e)
+        }
     }
 
-    pub async fn update_torrents(&self) -> Result<(), ()> {
-        println!("Updating torrents..");
-        let torrents = self.database.get_all_torrent_ids().await?;
+    // every page is stored in its own transaction, on shutdown the update stops
+    // between pages so no transaction is cut short
+    pub async fn update_torrents(&self, shutdown: &watch::Receiver<bool>) -> Result<(), ServiceError> {
+        tracing::info!("updating torrents");
+        const PAGE_SIZE: i64 = 1000;
+        let settings = self.cfg.settings.read().await;
+
+        let record_history = settings.database.stats_history_enabled;
+        let concurrency = settings.tracker.scrape_concurrency;
+        let timeout = Duration::from_secs(settings.tracker.scrape_timeout_seconds);
+        let scrape_url = settings.tracker.scrape_url.clone();
+        let batch_size = settings.tracker.scrape_batch_size;
+        let retry_policy = RetryPolicy {
+            retries: settings.tracker.scrape_retries,
+            backoff_base: Duration::from_millis(settings.tracker.scrape_backoff_base_ms),
+            backoff_max: Duration::from_millis(settings.tracker.scrape_backoff_max_ms),
+        };
+
+        drop(settings);
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|_| ServiceError::InternalServerError)?;
+
+        let mut offset = 0;
+
+        loop {
+            if *shutdown.borrow() {
+                tracing::info!("shutting down, stopped updating torrents");
+                break
+            }
+
+            let torrents = self.database.get_torrent_ids_paginated(PAGE_SIZE, offset).await?;
+            let count = torrents.len() as i64;
+            let info_hashes: Vec<String> = torrents.into_iter().map(|torrent| torrent.info_hash).collect();
+
+            let report = match &scrape_url {
+                Some(scrape_url) => {
+                    let mut report = ScrapeReport::default();
+
+                    // a failed batch is skipped, its torrents keep their current numbers
+                    for batch in info_hashes.chunks(batch_size.max(1)) {
+                        match retry_with_backoff(&retry_policy, timeout, || http_scrape(&client, scrape_url, batch)).await {
+                            Ok(batch_report) => {
+                                report.results.extend(batch_report.results);
+                                report.failed.extend(batch_report.failed);
+                            }
+                            Err(e) => {
+                                tracing::warn!(%scrape_url, error = %e, "http scrape failed");
+                                report.failed.extend_from_slice(batch);
+                            }
+                        }
+                    }
+
+                    report
+                }
+                None => scrape_concurrently(info_hashes, concurrency, timeout, &retry_policy, |info_hash| self.scrape_torrent(info_hash)).await
+            };
+
+            let updates: Vec<(String, i64, i64)> = report.results
+                .into_iter()
+                .map(|result| (result.info_hash, result.seeders, result.leechers))
+                .collect();
+
+            self.database.update_tracker_info_batch(&updates, record_history).await?;
+            self.database.record_scrape_failures(&report.failed).await?;
 
-        for torrent in torrents {
-            let _ = self.get_torrent_info(&torrent.info_hash).await;
+            if count < PAGE_SIZE { break }
+            offset += count;
         }
 
         Ok(())
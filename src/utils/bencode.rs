@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use serde_bencode::value::Value;
+use sha1::{Digest, Sha1};
+use crate::errors::ServiceError;
+
+// deeper nesting than this is never found in real torrents, it only protects the scanner's stack
+const MAX_DEPTH: usize = 64;
+
+// v1 piece hashes are sha-1
+const PIECE_HASH_LENGTH: usize = 20;
+
+#[derive(Debug)]
+pub struct ParsedTorrent {
+    pub info_hash: String,
+    pub name: String,
+    pub file_size: i64,
+    // (path, length), single file torrents have one entry named after the torrent
+    pub files: Vec<(String, i64)>,
+    pub piece_length: i64,
+    // length of `pieces` / 20, `None` for v2 only torrents, which have no `pieces`
+    pub piece_count: Option<i64>,
+}
+
+// derives the metadata from the raw .torrent bytes, the info hash is the sha-1 of the
+// `info` dictionary exactly as it appears in `bytes`
+pub fn parse_torrent(bytes: &[u8]) -> Result<ParsedTorrent, ServiceError> {
+    let info_bytes = find_info_dict(bytes).ok_or(ServiceError::InvalidTorrentFile)?;
+
+    let info = match serde_bencode::from_bytes::<Value>(&info_bytes) {
+        Ok(Value::Dict(info)) => info,
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let name = match info.get(&b"name"[..]) {
+        Some(Value::Bytes(name)) => String::from_utf8_lossy(name).to_string(),
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let files = match (info.get(&b"length"[..]), info.get(&b"files"[..])) {
+        (Some(Value::Int(length)), _) => vec![(name.clone(), *length)],
+        (None, Some(Value::List(files))) => files.iter().map(parse_file).collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let file_size = files.iter().map(|(_, length)| length).sum();
+
+    let piece_length = match info.get(&b"piece length"[..]) {
+        Some(Value::Int(piece_length)) if *piece_length > 0 => *piece_length,
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    // `pieces` is the concatenated 20 byte sha-1 of every piece
+    let piece_count = match info.get(&b"pieces"[..]) {
+        Some(Value::Bytes(pieces)) if !pieces.is_empty() && pieces.len() % PIECE_HASH_LENGTH == 0 => {
+            Some((pieces.len() / PIECE_HASH_LENGTH) as i64)
+        }
+        Some(_) => return Err(ServiceError::InvalidTorrentFile),
+        None if info.contains_key(&b"meta version"[..]) => None,
+        None => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let mut hasher = Sha1::new();
+    hasher.update(&info_bytes);
+
+    let mut buffer = [0u8; 40];
+    let info_hash = match binascii::bin2hex(hasher.finalize().as_slice(), &mut buffer) {
+        Ok(hex) => String::from_utf8_lossy(hex).to_string(),
+        Err(_) => return Err(ServiceError::InternalServerError)
+    };
+
+    Ok(ParsedTorrent {
+        info_hash,
+        name,
+        file_size,
+        files,
+        piece_length,
+        piece_count,
+    })
+}
+
+// a multi file entry, eg: { "length": 1024, "path": ["dir", "file.txt"] }
+fn parse_file(file: &Value) -> Result<(String, i64), ServiceError> {
+    let file: &HashMap<Vec<u8>, Value> = match file {
+        Value::Dict(file) => file,
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let length = match file.get(&b"length"[..]) {
+        Some(Value::Int(length)) if *length >= 0 => *length,
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    let path = match file.get(&b"path"[..]) {
+        Some(Value::List(segments)) if !segments.is_empty() => segments.iter()
+            .map(|segment| match segment {
+                Value::Bytes(segment) => Ok(String::from_utf8_lossy(segment).to_string()),
+                _ => Err(ServiceError::InvalidTorrentFile)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("/"),
+        _ => return Err(ServiceError::InvalidTorrentFile)
+    };
+
+    Ok((path, length))
+}
+
+// the raw bytes of the top level `info` value
+fn find_info_dict(bytes: &[u8]) -> Option<Vec<u8>> {
+    top_level_entries(bytes)?
+        .into_iter()
+        .find(|(key, _)| key == b"info")
+        .map(|(_, value)| value)
+}
+
+fn string_value(bytes: &[u8]) -> Option<&[u8]> {
+    let colon = bytes.iter().position(|&b| b == b':')?;
+    bytes.get(colon + 1..)
+}
+
+// end offset of the bencoded value starting at `start`
+fn value_end(bytes: &[u8], start: usize, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH { return None }
+
+    match bytes.get(start)? {
+        b'i' => Some(start + bytes[start..].iter().position(|&b| b == b'e')? + 1),
+        b'l' | b'd' => {
+            let mut pos = start + 1;
+            while *bytes.get(pos)? != b'e' {
+                pos = value_end(bytes, pos, depth + 1)?;
+            }
+            Some(pos + 1)
+        }
+        b'0'..=b'9' => {
+            let colon = start + bytes[start..].iter().position(|&b| b == b':')?;
+            let length: usize = std::str::from_utf8(&bytes[start..colon]).ok()?.parse().ok()?;
+            let end = colon.checked_add(1)?.checked_add(length)?;
+            if end > bytes.len() { return None }
+            Some(end)
+        }
+        _ => None
+    }
+}
+
+// sets `announce` to the first of `announce_urls` and writes a BEP 12 `announce-list` with one tier
+// per url, in order, followed by the tiers the torrent already had (minus the urls set here).
+// every other value including the `info` dictionary is copied byte for byte so the info hash stays the same
+pub fn rewrite_announce(torrent_bytes: &[u8], announce_urls: &[String]) -> Result<Vec<u8>, ServiceError> {
+    let announce = announce_urls.first().ok_or(ServiceError::InternalServerError)?;
+
+    let mut entries = top_level_entries(torrent_bytes).ok_or(ServiceError::InvalidTorrentFile)?;
+
+    let mut announce_tiers: Vec<Value> = announce_urls.iter()
+        .map(|url| Value::List(vec![Value::Bytes(url.as_bytes().to_vec())]))
+        .collect();
+    let mut has_announce_list = false;
+
+    entries.retain(|(key, value)| match &key[..] {
+        b"announce" => false,
+        b"announce-list" => {
+            has_announce_list = true;
+            if let Ok(Value::List(tiers)) = serde_bencode::from_bytes::<Value>(value) {
+                for tier in tiers {
+                    if let Value::List(urls) = tier {
+                        let urls: Vec<Value> = urls.into_iter()
+                            .filter(|url| !matches!(url, Value::Bytes(url) if announce_urls.iter().any(|a| a.as_bytes() == &url[..])))
+                            .collect();
+
+                        if !urls.is_empty() { announce_tiers.push(Value::List(urls)) }
+                    }
+                }
+            }
+            false
+        }
+        _ => true
+    });
+
+    entries.push((b"announce".to_vec(), encode_value(&Value::Bytes(announce.as_bytes().to_vec()))?));
+
+    if has_announce_list || announce_tiers.len() > 1 {
+        entries.push((b"announce-list".to_vec(), encode_value(&Value::List(announce_tiers))?));
+    }
+
+    // bencoded dictionaries are sorted by key
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut bytes = vec![b'd'];
+    for (key, value) in entries {
+        bytes.extend_from_slice(format!("{}:", key.len()).as_bytes());
+        bytes.extend_from_slice(&key);
+        bytes.extend_from_slice(&value);
+    }
+    bytes.push(b'e');
+
+    Ok(bytes)
+}
+
+fn encode_value(value: &Value) -> Result<Vec<u8>, ServiceError> {
+    serde_bencode::to_bytes(value).map_err(|_| ServiceError::InternalServerError)
+}
+
+// (key, raw bencoded value) pairs of the top level dictionary
+fn top_level_entries(bytes: &[u8]) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+    if bytes.first() != Some(&b'd') { return None }
+
+    let mut entries = vec![];
+    let mut pos = 1;
+
+    while *bytes.get(pos)? != b'e' {
+        let key_end = value_end(bytes, pos, 0)?;
+        let key = string_value(&bytes[pos..key_end])?;
+        let value_end = value_end(bytes, key_end, 0)?;
+
+        entries.push((key.to_vec(), bytes[key_end..value_end].to_vec()));
+
+        pos = value_end;
+    }
+
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewriting_the_announce_keeps_the_info_hash() {
+        let pieces = "x".repeat(20);
+        let torrent = format!(
+            "d8:announce14:udp://old:696913:announce-listll14:udp://old:6969el13:udp://other:1ee4:infod6:lengthi100e4:name9:movie.mkv12:piece lengthi16384e6:pieces20:{}7:privatei1eee",
+            pieces
+        );
+        let original = parse_torrent(torrent.as_bytes()).unwrap();
+
+        let announce_urls = vec!["udp://tracker:6969/key".to_string(), "udp://mirror:6969".to_string()];
+        let rewritten = rewrite_announce(torrent.as_bytes(), &announce_urls).unwrap();
+
+        assert_eq!(parse_torrent(&rewritten).unwrap().info_hash, original.info_hash);
+
+        let rewritten = match serde_bencode::from_bytes::<Value>(&rewritten).unwrap() {
+            Value::Dict(rewritten) => rewritten,
+            _ => panic!("not a dictionary")
+        };
+        assert_eq!(rewritten.get(&b"announce"[..]), Some(&Value::Bytes(b"udp://tracker:6969/key".to_vec())));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::errors::ServiceError;
+
+// quotes a field when it contains a separator, quote or line break, quotes inside are doubled,
+// eg: `say "hi", bye` -> `"say ""hi"", bye"`
+pub fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn write_record<W: std::io::Write>(writer: &mut W, fields: &[String]) -> std::io::Result<()> {
+    let record: Vec<String> = fields.iter().map(|field| escape_field(field)).collect();
+    writer.write_all(record.join(",").as_bytes())?;
+    writer.write_all(b"\r\n")
+}
+
+// splits csv text into records of fields, one record at a time. fields may be quoted as written
+// by `write_record`. an unterminated quote swallows the rest of the input and ends in an error
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        chars: input.chars().peekable(),
+        done: false,
+    }
+}
+
+pub struct Records<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    done: bool,
+}
+
+impl Iterator for Records<'_> {
+    type Item = Result<Vec<String>, ServiceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None }
+
+        let mut record = vec![];
+        let mut field = String::new();
+        let mut in_quotes = false;
+
+        while let Some(c) = self.chars.next() {
+            if in_quotes {
+                match c {
+                    '"' if self.chars.peek() == Some(&'"') => {
+                        self.chars.next();
+                        field.push('"');
+                    }
+                    '"' => in_quotes = false,
+                    _ => field.push(c)
+                }
+                continue
+            }
+
+            match c {
+                '"' => in_quotes = true,
+                ',' => record.push(std::mem::take(&mut field)),
+                '\r' if self.chars.peek() == Some(&'\n') => {}
+                '\n' | '\r' => {
+                    record.push(field);
+                    return Some(Ok(record))
+                }
+                _ => field.push(c)
+            }
+        }
+
+        self.done = true;
+
+        if in_quotes { return Some(Err(ServiceError::BadRequest)) }
+
+        // the last line doesn't have to end with a line break
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            return Some(Ok(record))
+        }
+
+        None
+    }
+}
@@ -0,0 +1,37 @@
+use sha1::{Digest, Sha1};
+use crate::errors::ServiceError;
+
+// v1 (sha-1) and v2 (sha-256, BEP 52) info hashes as hex
+const INFO_HASH_V1_LENGTH: usize = 40;
+const INFO_HASH_V2_LENGTH: usize = 64;
+
+pub fn validate_info_hash(info_hash: &str) -> Result<String, ServiceError> {
+    let info_hash = info_hash.trim();
+
+    if info_hash.len() != INFO_HASH_V1_LENGTH && info_hash.len() != INFO_HASH_V2_LENGTH {
+        return Err(ServiceError::InvalidInfoHash)
+    }
+
+    if !info_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ServiceError::InvalidInfoHash)
+    }
+
+    Ok(info_hash.to_lowercase())
+}
+
+// sha-1 (hex) of the (path, length) pairs sorted by path, so the same files give the same hash
+// whatever their order in the torrent or its piece length. None for an empty file list
+pub fn content_hash(files: &[(String, i64)]) -> Option<String> {
+    if files.is_empty() { return None }
+
+    let mut files: Vec<&(String, i64)> = files.iter().collect();
+    files.sort();
+
+    let mut hasher = Sha1::new();
+    for (path, length) in files {
+        // the path length keeps "a" + "1b" apart from "a1" + "b"
+        hasher.update(format!("{}:{}:{}\n", path.len(), path, length).as_bytes());
+    }
+
+    Some(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
@@ -0,0 +1,18 @@
+// v2 info hashes are sha-256 multihashes, 0x12 is the sha2-256 code and 0x20 the digest length
+const MULTIHASH_SHA256_PREFIX: &str = "1220";
+
+pub fn build_magnet(info_hash: &str, display_name: &str, trackers: &[String]) -> String {
+    let exact_topic = if info_hash.len() == 64 {
+        format!("urn:btmh:{}{}", MULTIHASH_SHA256_PREFIX, info_hash)
+    } else {
+        format!("urn:btih:{}", info_hash)
+    };
+
+    let mut magnet = format!("magnet:?xt={}&dn={}", exact_topic, urlencoding::encode(display_name));
+
+    for tracker in trackers {
+        magnet.push_str(&format!("&tr={}", urlencoding::encode(tracker)));
+    }
+
+    magnet
+}
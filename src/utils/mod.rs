@@ -1,2 +1,10 @@
 pub mod parse_torrent;
 pub mod time;
+pub mod hash;
+pub mod token;
+pub mod route;
+pub mod magnet;
+pub mod bencode;
+pub mod csv;
+pub mod size;
+pub mod similarity;
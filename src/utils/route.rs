@@ -0,0 +1,15 @@
+use crate::errors::ServiceError;
+
+// "/About " and "about" are the same route, eg: "/Help-Center" becomes "help-center"
+pub fn normalize_route(route: &str) -> Result<String, ServiceError> {
+    let route = route.trim().to_lowercase();
+    let route = route.strip_prefix('/').unwrap_or(&route);
+
+    if route.is_empty() { return Err(ServiceError::InvalidRoute) }
+
+    if !route.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(ServiceError::InvalidRoute)
+    }
+
+    Ok(route.to_string())
+}
@@ -0,0 +1,58 @@
+// words that say nothing about the release, skipped when picking the token to pre-filter on
+const STOP_WORDS: [&str; 6] = ["the", "a", "an", "and", "of", "in"];
+const MIN_SIGNIFICANT_TOKEN_LEN: usize = 3;
+
+// lowercased alphanumeric runs, eg: "The.Matrix.1999.1080p" -> ["the", "matrix", "1999", "1080p"]
+pub fn tokenize(title: &str) -> Vec<String> {
+    title.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect()
+}
+
+// first token that isn't a stop word and has a few characters, is None for titles like "The 1"
+pub fn first_significant_token(title: &str) -> Option<String> {
+    tokenize(title).into_iter()
+        .find(|token| token.chars().count() >= MIN_SIGNIFICANT_TOKEN_LEN && !STOP_WORDS.contains(&token.as_str()))
+}
+
+// token set ratio between 0.0 and 1.0: both titles are reduced to their sorted, deduplicated
+// tokens and compared with a normalized levenshtein distance, so separators, case and word order
+// don't matter. eg: "Matrix (1999) 1080p" and "the.matrix.1999.1080p" score 0.81
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = token_set(a);
+    let b = token_set(b);
+
+    let max_len = a.len().max(b.len());
+
+    if max_len == 0 { return 1.0 }
+
+    1.0 - levenshtein(&a, &b) as f64 / max_len as f64
+}
+
+fn token_set(title: &str) -> Vec<char> {
+    let mut tokens = tokenize(title);
+    tokens.sort();
+    tokens.dedup();
+
+    tokens.join(" ").chars().collect()
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
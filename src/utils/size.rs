@@ -0,0 +1,22 @@
+const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+// eg: 0 -> "0 B", 1536 -> "1.50 KiB", 1_503_238_553 -> "1.40 GiB".
+// negative sizes keep their sign instead of failing
+pub fn format_bytes(bytes: i64) -> String {
+    let sign = if bytes < 0 { "-" } else { "" };
+    let bytes = bytes.unsigned_abs();
+
+    if bytes < 1024 { return format!("{}{} B", sign, bytes) }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    // an i64 stays below 8 EiB, so the units never run out
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{}{:.2} {}", sign, size, UNITS[unit])
+}
+
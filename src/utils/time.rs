@@ -1,7 +1,85 @@
 use std::time::SystemTime;
+use chrono::{DateTime, Utc};
 
 pub fn current_time() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH).unwrap()
         .as_secs()
 }
+
+pub fn current_time_utc() -> DateTime<Utc> {
+    Utc::now()
+}
+
+// eg: "2022-04-02T12:00:00Z", `None` when the timestamp is out of range
+pub fn epoch_to_rfc3339(timestamp: i64) -> Option<String> {
+    use chrono::{NaiveDateTime, SecondsFormat};
+
+    let datetime = NaiveDateTime::from_timestamp_opt(timestamp, 0)?;
+
+    Some(DateTime::<Utc>::from_utc(datetime, Utc).to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+// stores timestamps as unix seconds but exposes them as RFC 3339 strings,
+// use with `#[serde(with = "crate::utils::time::serde_epoch_as_rfc3339")]`
+pub mod serde_epoch_as_rfc3339 {
+    use chrono::DateTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(timestamp: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        let datetime = super::epoch_to_rfc3339(*timestamp)
+            .ok_or_else(|| serde::ser::Error::custom("timestamp out of range"))?;
+
+        serializer.serialize_str(&datetime)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        DateTime::parse_from_rfc3339(&s)
+            .map(|datetime| datetime.timestamp())
+            .map_err(D::Error::custom)
+    }
+}
+
+// `serde_epoch_as_rfc3339` for optional timestamps, `None` stays null,
+// use with `#[serde(default, with = "crate::utils::time::serde_option_epoch_as_rfc3339")]`
+pub mod serde_option_epoch_as_rfc3339 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(timestamp: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(timestamp) => super::serde_epoch_as_rfc3339::serialize(timestamp, serializer),
+            None => serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Timestamp(#[serde(with = "super::serde_epoch_as_rfc3339")] i64);
+
+        Ok(Option::<Timestamp>::deserialize(deserializer)?.map(|Timestamp(timestamp)| timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Login {
+        #[serde(default, with = "super::serde_option_epoch_as_rfc3339")]
+        last_login: Option<i64>,
+    }
+
+    #[test]
+    fn optional_timestamps_round_trip_as_rfc3339_or_null() {
+        let json = serde_json::to_string(&Login { last_login: Some(1650000000) }).unwrap();
+        assert_eq!(json, r#"{"last_login":"2022-04-15T05:20:00Z"}"#);
+        assert_eq!(serde_json::from_str::<Login>(&json).unwrap(), Login { last_login: Some(1650000000) });
+
+        assert_eq!(serde_json::to_string(&Login { last_login: None }).unwrap(), r#"{"last_login":null}"#);
+        assert_eq!(serde_json::from_str::<Login>("{}").unwrap(), Login { last_login: None });
+    }
+}
@@ -0,0 +1,25 @@
+use rand_core::{OsRng, RngCore};
+
+// random 32 byte token as hex, for single-use links like password resets
+pub fn generate_token() -> String {
+    random_hex::<32, 64>()
+}
+
+// random 32 character tracker key
+pub fn generate_tracker_key() -> String {
+    random_hex::<16, 32>()
+}
+
+// random 16 character id, to find the log lines of a single request
+pub fn generate_request_id() -> String {
+    random_hex::<8, 16>()
+}
+
+fn random_hex<const BYTES: usize, const CHARS: usize>() -> String {
+    let mut bytes = [0u8; BYTES];
+    OsRng.fill_bytes(&mut bytes);
+
+    let mut buffer = [0u8; CHARS];
+    let hex = binascii::bin2hex(&bytes, &mut buffer).ok().unwrap();
+    String::from(std::str::from_utf8(hex).unwrap())
+}